@@ -0,0 +1,46 @@
+use {vector::MinMax, crate::{music_xml::{Note, Notation, Articulations, ArticulationData, Placement, StemDirection}, font::SMuFL::{articulation, brass}, measure::MeasureLayoutContext}};
+impl MeasureLayoutContext<'_> {
+	// Stacks articulations outwards on the notehead side opposite the stem (or per placement). Staccato and tenuto stay in staff spaces, others go outside the staff
+	// Returns the outer edges for further notations
+	pub fn articulations(&mut self, staff: usize, chord: &[&Note], bounds: MinMax<i8>, steps: MinMax<i8>, MinMax{min: left, max: right}: MinMax<i32>, direction: Option<StemDirection>) -> MinMax<i8> {
+		let mut articulations = chord.iter().flat_map(|note| note.notations()).filter_map(|notation| if let Notation::Articulations(Articulations{content}) = notation { Some(content) } else { None }).flatten().collect::<Vec<_>>();
+		articulations.sort_by_key(|articulation| articulation.order());
+		let half = (self.sheet.staff_height/8) as i32;
+		let space = self.space() as i32;
+		let (mut above, mut below) = (steps.max + 1, steps.min - 1); // Outer edges
+		for articulation in articulations {
+			use ArticulationData::*;
+			let glyph = match articulation {
+				Accent(_) => articulation::accent, StrongAccent(_) => articulation::marcato, Staccato(_) => articulation::staccato, Tenuto(_) => articulation::tenuto,
+				DetachedLegato(_) => articulation::tenuto_staccato, Staccatissimo(_) => articulation::staccatissimo, Spiccato(_) => articulation::staccatissimo_stroke,
+				Stress(_) => articulation::stress, Unstress(_) => articulation::unstress, SoftAccent(_) => articulation::soft_accent,
+				Scoop(_)|Plop(_)|Doit(_)|Falloff(_)|BreathMark(_)|Caesura(_) => { // Beside the note
+					let (glyph, step) = match articulation { Scoop(_) => (brass::scoop, bounds.min), Plop(_) => (brass::plop, bounds.max), Doit(_) => (brass::doit, bounds.max), Falloff(_) => (brass::falloff, bounds.max),
+						BreathMark(_) => (articulation::breath_mark, std::cmp::max(bounds.max + 2, 10)), _ => (articulation::caesura, 4) };
+					let x = if let Scoop(_)|Plop(_) = articulation { left - self.sheet.advance(glyph) - space/4 } else { right + space/2 };
+					self.push_glyph(x as u32, staff, step, 0, glyph);
+					continue;
+				}
+			};
+			let above_side = match articulation.placement() { Some(Placement::Above) => true, Some(Placement::Below) => false, None => !matches!(direction, Some(StemDirection::Up)) };
+			let glyph = if above_side { glyph } else { articulation::below(glyph) };
+			let bbox = self.sheet.bbox(glyph);
+			let height = (((bbox.y_max - bbox.y_min) as i32 + half - 1) / half) as i8;
+			let inside = articulation.order() <= 2;
+			let center = if above_side {
+				let center = above + 1 + height/2;
+				let center = if !inside { std::cmp::max(center, 9 + height/2) } else if (0..=8).contains(&center) && center % 2 == 0 { center + 1 } else { center }; // Avoids staff lines
+				above = center + (height+1)/2;
+				center
+			} else {
+				let center = below - 1 - height/2;
+				let center = if !inside { std::cmp::min(center, -1 - height/2) } else if (0..=8).contains(&center) && center % 2 == 0 { center - 1 } else { center };
+				below = center - (height+1)/2;
+				center
+			};
+			let x = (left + right - self.sheet.advance(glyph)) / 2;
+			self.push_glyph(x as u32, staff, center, self.sheet.center(glyph), glyph);
+		}
+		MinMax{min: below, max: above}
+	}
+}
@@ -1,31 +1,55 @@
-use crate::{music_xml::{Attributes, Clef, ClefSign, Step, Pitch, Key, Time}, font::SMuFL::{clef, accidental, time_signature}, measure::MeasureLayoutContext, staff::{Staff, IndexMut, StaffRef}};
-impl MeasureLayoutContext<'_> { pub fn attributes(&mut self, staves: &mut [Staff], Attributes{clefs, key, time, ..}: &Attributes) {
-	for &clef@Clef{staff, sign, ..} in clefs {
-		let mut staff = staves.index_mut(&staff);
-		staff.clef = Some(clef);
-		let (glyph, step) = {use ClefSign::*; match sign { G=>(clef::G, Step::G), F=>(clef::F, Step::F) }};
-		let x = self.x;
-		self.push_glyph_at_pitch(x, staff.as_ref(), &Pitch::new(&clef, &step), glyph);
+use crate::{music_xml::{Attributes, Clef, ClefSign, Step, Key, NonTraditionalKey, Cancel, CancelLocation, Time, Color}, font::SMuFL::{clef, accidental, time_signature}, measure::MeasureLayoutContext, staff::{Staff, IndexMut}};
+
+// Sharps or flats of a traditional key in order
+fn traditional(fifths: i8) -> Vec<(Step, f32)> {
+	use Step::*;
+	let (steps, alter) = if fifths > 0 { ([F,C,G,D,A,E,B], 1.) } else { ([B,E,A,D,G,C,F], -1.) };
+	steps.into_iter().take(fifths.unsigned_abs() as usize).map(|step| (step, alter)).collect()
+}
+// Steps and alterations of a key, either traditional or in the key-step/key-alter pairs of a non-traditional key
+fn alterations(Key{fifths, non_traditional, ..}: &Key) -> Vec<(Step, f32)> {
+	if let Some(fifths) = fifths { return traditional(*fifths); }
+	let steps = non_traditional.iter().filter_map(|element| if let NonTraditionalKey::KeyStep(step) = element { Some(*step) } else { None });
+	let alters = non_traditional.iter().filter_map(|element| if let NonTraditionalKey::KeyAlter(alter) = element { Some(*alter) } else { None });
+	steps.zip(alters).collect()
+}
+fn glyph(alter: f32) -> char { use accidental::*; if alter >= 1.5 { double_sharp } else if alter > 0. { sharp } else if alter == 0. { natural } else if alter > -1.5 { flat } else { double_flat } }
+
+impl MeasureLayoutContext<'_> {
+	fn clefs(&mut self, staves: &mut [Staff], clefs: &[Clef], scale: f32) {
+		for &clef@Clef{staff, sign, color, ..} in clefs {
+			let mut staff = staves.index_mut(&staff);
+			staff.clef = Some(clef);
+			let glyph = {use ClefSign::*; match sign { G=>clef::G, F=>clef::F, C=>clef::C }};
+			let (x, index) = (self.x, staff.as_ref().index);
+			self.set_color(color);
+			self.measure.scale = scale;
+			self.push_glyph(x, index, clef.step(), 0, glyph);
+		}
+		self.measure.scale = 1.;
+		self.set_color(None);
 	}
-	self.advance(0);
-	if let &Some(Key{fifths,..}) = key {
-		let mut key = |fifths:i8, symbol| {
-			let mut sign = |steps: &mut dyn Iterator<Item=&Step>| {
-				for step in steps {
-					for (index, Staff{clef, ..}) in staves.iter().enumerate() {
-						let x = self.x;
-						self.push_glyph_at_pitch(x, StaffRef{index, staff: &Staff{clef: *clef, octave: 0}}, &Pitch::new(clef.as_ref().unwrap(), step), symbol);
-					}
-				}
-			};
-			let steps = {use Step::*; [B,E,A,D,G,C,F].iter()};
-			if fifths>0 { sign(&mut steps.rev().take(fifths as usize)) } else { sign(&mut steps.take((-fifths) as usize)) }
-		};
-		//if fifths == 0 { key(system.fifths, accidental::natural) } else
-		key(fifths, if fifths<0 { accidental::flat } else { accidental::sharp });
+	// Accidentals of the key one after the other, each at the position of its step in the pattern of the clef (naturals at the position of the cancelled accidental).
+	// Naturals cancel the accidentals of the previous key whose steps are not in the new key, before the new key unless located right of it
+	fn key_signature(&mut self, staves: &mut [Staff], key: Vec<(Step, f32)>, cancel: Option<(Vec<(Step, f32)>, CancelLocation)>, color: Option<Color>) {
+		let (naturals, location) = cancel.map_or((vec![], CancelLocation::Left), |(previous, location)| (previous.into_iter().filter(|(step, _)| !key.iter().any(|(other, _)| i8::from(other) == i8::from(step))).map(|(step, alter)| (step, alter, accidental::natural)).collect(), location));
+		let accidentals = key.iter().map(|&(step, alter)| (step, alter, glyph(alter))).collect::<Vec<_>>();
+		let (first, second) = if let CancelLocation::Right = location { (accidentals, naturals) } else { (naturals, accidentals) };
+		self.set_color(color);
+		for (step, alter, glyph) in first.into_iter().chain(second) {
+			let x = self.x;
+			for (index, staff) in staves.iter().enumerate() {
+				let step = staff.key_step(&step, alter > 0.);
+				self.push_glyph(x, index, step, 0, glyph);
+			}
+			self.x += self.sheet.advance(glyph) as u32;
+		}
+		self.set_color(None);
+		for staff in staves.iter_mut() { staff.key = key.clone(); }
 		self.advance(0);
 	}
-	if let Some(Time{beats, beat_type,..}) = time {
+	fn time(&mut self, staves: &[Staff], &Time{beats, beat_type, color}: &Time) {
+		self.set_color(color);
 		let texts : [String; 2] = [beats, beat_type].map(|number| number.to_string().chars().map(time_signature::from).collect::<String>());
 		use ui::text::{Plain, View, layout, Glyph, unicode_segmentation::UnicodeSegmentation};
 		let mut texts : [_; 2] = texts.map(|text| View::new_with_face(&self.measure.sheet.face, Plain(text)));
@@ -38,5 +62,35 @@ impl MeasureLayoutContext<'_> { pub fn attributes(&mut self, staves: &mut [Staff
 				}
 			}
 		}
+		self.set_color(None);
+	}
+	// Clefs (smaller within the measure or as courtesy), key (cancelling the previous one unless at a system start) and time
+	fn changes(&mut self, staves: &mut [Staff], Attributes{clefs, key, time, ..}: &Attributes, clef_scale: f32, naturals: bool) {
+		self.clefs(staves, clefs, clef_scale);
+		self.advance(0);
+		if let Some(key@Key{cancel, color, ..}) = key {
+			let (previous, location) = cancel.as_ref().map_or((staves[0].key.clone(), CancelLocation::Left), |&Cancel{fifths, location}| (traditional(fifths), location.unwrap_or(CancelLocation::Left)));
+			self.key_signature(staves, alterations(key), naturals.then(|| (previous, location)), *color);
+		}
+		if let Some(time) = time { self.time(staves, time); }
+	}
+	pub fn attributes(&mut self, staves: &mut [Staff], attributes: &Attributes) {
+		let scale = if self.t > 0 { self.sheet.cue_size } else { 1. };
+		self.changes(staves, attributes, scale, !self.system_start);
+	}
+	// Changes of the first attributes of the next measure, at the end of a system. Returns the right
+	pub fn courtesy(&mut self, staves: &mut [Staff], attributes: &Attributes) -> u32 {
+		self.changes(staves, attributes, self.sheet.cue_size, true);
+		self.advance(0);
+		self.x
+	}
+	// Clefs and key reprinted at a system start, unless changed by the first attributes of the measure
+	pub fn system_start(&mut self, staves: &mut [Staff], leading: Option<&Attributes>) {
+		self.system_start = true;
+		let changed = |index: usize| leading.map_or(false, |Attributes{clefs, ..}| clefs.iter().any(|clef| usize::from(&clef.staff) == index));
+		let clefs = staves.iter().enumerate().filter(|&(index, _)| !changed(index)).filter_map(|(_, staff)| staff.clef).collect::<Vec<_>>();
+		self.clefs(staves, &clefs, 1.);
+		if !(0..staves.len()).any(changed) { self.advance(0); } // Changed clefs are drawn aligned by the attributes
+		if leading.map_or(true, |Attributes{key, ..}| key.is_none()) { let key = staves[0].key.clone(); self.key_signature(staves, key, None, None); }
 	}
-}}
+}
@@ -0,0 +1,88 @@
+use {ui::graphic::vertical, crate::{music_xml::{Barline, BarStyle, Repeat, BackwardForward, Ending, StartStopDiscontinue, RightLeftMiddle, UprightInverted}, font::SMuFL::{EngravingDefaults, repeat}, staff::Staff, spanner::{Event, Point, Kind}, measure::{Measure, MeasureLayoutContext}}};
+
+enum Part { Line(u32), DashedLine, DottedLine, Dots, Gap(u32) }
+
+impl Measure<'_> {
+	// Components from left to right. Repeat dots are on the repeated side
+	fn barline_parts(&self, style: Option<BarStyle>, repeat: Option<BackwardForward>) -> Vec<Part> {
+		let &EngravingDefaults{thin_barline_thickness: thin, thick_barline_thickness: thick, barline_separation, thin_thick_barline_separation, repeat_barline_dot_separation, ..} = &self.sheet.engraving_defaults;
+		use Part::*;
+		let style = style.unwrap_or(match repeat { Some(BackwardForward::Backward) => BarStyle::LightHeavy, Some(BackwardForward::Forward) => BarStyle::HeavyLight, None => BarStyle::Regular });
+		let lines = match style {
+			BarStyle::Regular|BarStyle::Tick|BarStyle::Short => vec![Line(thin)], BarStyle::Dashed => vec![DashedLine], BarStyle::Dotted => vec![DottedLine], BarStyle::Heavy => vec![Line(thick)],
+			BarStyle::LightLight => vec![Line(thin), Gap(barline_separation), Line(thin)], BarStyle::HeavyHeavy => vec![Line(thick), Gap(barline_separation), Line(thick)],
+			BarStyle::LightHeavy => vec![Line(thin), Gap(thin_thick_barline_separation), Line(thick)], BarStyle::HeavyLight => vec![Line(thick), Gap(thin_thick_barline_separation), Line(thin)],
+			BarStyle::None => vec![],
+		};
+		match repeat {
+			Some(BackwardForward::Backward) => vec![Dots, Gap(repeat_barline_dot_separation)].into_iter().chain(lines).collect(),
+			Some(BackwardForward::Forward) => lines.into_iter().chain(vec![Gap(repeat_barline_dot_separation), Dots]).collect(),
+			None => lines
+		}
+	}
+	fn part_width(&self, part: &Part) -> i32 {
+		let thin = self.sheet.engraving_defaults.thin_barline_thickness;
+		match *part { Part::Line(thickness) => thickness as i32, Part::DashedLine => thin as i32, Part::DottedLine => 2 * thin as i32, Part::Dots => self.sheet.advance(repeat::dot), Part::Gap(gap) => gap as i32 }
+	}
+	pub fn barline_width(&self, style: Option<BarStyle>, repeat: Option<BackwardForward>) -> i32 { self.barline_parts(style, repeat).iter().map(|part| self.part_width(part)).sum() }
+	// Draws a barline across the staves from its left edge at x
+	pub fn barline(&mut self, staves: usize, x: i32, style: Option<BarStyle>, repeat: Option<BackwardForward>) {
+		let space = (self.sheet.staff_height/4) as i32;
+		let thin = self.sheet.engraving_defaults.thin_barline_thickness;
+		let spans = match style {
+			Some(BarStyle::Tick) => vec![(self.sheet.y(staves-1, 10), self.sheet.y(staves-1, 6))], // Through the top line
+			Some(BarStyle::Short) => (0..staves).map(|staff| (self.sheet.y(staff, 6), self.sheet.y(staff, 2))).collect(),
+			_ => vec![(self.sheet.y(staves-1, 8), self.sheet.y(0, 0))]
+		};
+		let mut x = x;
+		for part in self.barline_parts(style, repeat) {
+			match part {
+				Part::Line(thickness) => self.graphic.rects.extend(spans.iter().map(|&(y0, y1)| vertical(x + thickness as i32/2, thickness, y0, y1))),
+				Part::DashedLine => for &(y0, y1) in &spans {
+					let mut y = y0;
+					while y < y1 { self.graphic.rects.push(vertical(x + thin as i32/2, thin, y, std::cmp::min(y + space/2, y1))); y += space*3/4; }
+				},
+				Part::DottedLine => for staff in 0..staves { for step in [1, 3, 5, 7] {
+					let y = self.sheet.y(staff, step);
+					self.graphic.rects.push(vertical(x + thin as i32, 2*thin, y - thin as i32, y + thin as i32));
+				}},
+				Part::Dots => for staff in 0..staves { for step in [3, 5] {
+					self.push_glyph(x as u32, staff, step, self.sheet.center(repeat::dot), repeat::dot);
+				}},
+				Part::Gap(_) => {}
+			}
+			x += self.part_width(&part);
+		}
+	}
+}
+
+impl MeasureLayoutContext<'_> {
+	// Draws left barlines at the start of the measure (right barlines are drawn by the system layout), starts and stops volta brackets, and
+	// centers fermatas on the barline, upright above the top staff, inverted below the bottom staff
+	pub fn barline(&mut self, staves: &[Staff], Barline{location, bar_style, repeat, ending, fermata}: &Barline) {
+		let x = self.x as i32;
+		let space = self.space() as i32;
+		let top = staves.len()-1;
+		for fermata in fermata {
+			let inverted = matches!(fermata.r#type, Some(UprightInverted::Inverted));
+			let (staff, mut edge) = if inverted { (0, -1) } else { (top, 9) };
+			self.push_stacked(x, staff, &mut edge, !inverted, &[crate::notation::fermata(fermata)], 1.);
+		}
+		if let Some(Ending{number, r#type, text, ..}) = ending {
+			let point = Point{kind: Kind::Ending(matches!(r#type, StartStopDiscontinue::Stop)), number: None, staff: top, step: 14, x};
+			if let StartStopDiscontinue::Start = r#type {
+				self.spanners.push(Event::Start(point));
+				let text = text.clone().filter(|text| !text.trim().is_empty()).unwrap_or_else(|| format!("{number}."));
+				let (y, size) = (self.y(top, 10), self.sheet.staff_height*2/3);
+				self.push_text(x + space/2, y, size, &text);
+			} else {
+				self.spanners.push(Event::Stop(point));
+			}
+		}
+		if let (RightLeftMiddle::Left, true) = (location, bar_style.is_some() || repeat.is_some()) { // In place of the barline ending the previous measure
+			let repeat = repeat.as_ref().map(|&Repeat{direction, ..}| direction);
+			self.measure.barline(staves.len(), x, *bar_style, repeat);
+			self.x += (self.measure.barline_width(*bar_style, repeat) + space/2) as u32;
+		}
+	}
+}
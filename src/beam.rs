@@ -1,61 +1,167 @@
-use crate::{music_xml::Note, staff::Staff, measure::MeasureLayoutContext};
+use crate::{music_xml::{Note, NoteType, NoteTypeValue, StemDirection, Grace}, font::SMuFL::{note_head, accidental}, staff::Staff, measure::MeasureLayoutContext};
+
+fn head(note: &Note) -> char { use {NoteTypeValue::*, note_head::*}; match note.r#type.as_ref().map(|NoteType{value}| value) { Some(Breve)=>breve, Some(Whole)=>whole, Some(Half)=>half, _=>black } }
+
+// Displaces the upper (stem up) or lower (stem down) head of seconds of a chord (of a single voice) to the opposite side of the stem.
+// Unisons of the same pitch share a head, chromatic unisons (or with different heads) are offset, each keeping its accidental
+fn heads<'t>(advance: &dyn Fn(char) -> i32, staves: &[Staff], chord: &[&'t Note], direction: StemDirection) -> Vec<(i32, i8, &'t Note)> {
+	let mut notes = chord.iter().filter_map(|&note| note.step(staves).map(|step| (step, note))).collect::<Vec<_>>();
+	notes.sort_by_key(|&(step,_)| step);
+	let side = if let StemDirection::Down = direction { notes.reverse(); -1 } else { 1 };
+	let mut heads = Vec::<(i32, i8, &Note)>::new();
+	for (step, note) in notes {
+		let dx = match heads.last() {
+			Some(&(_, last_step, last)) if last_step == step && note.staff.unwrap().0 == last.staff.unwrap().0 => if head(last) == head(note) && last.pitch().map(|pitch| pitch.alter) == note.pitch().map(|pitch| pitch.alter) { continue } else { advance(head(last)) },
+			Some(&(0, last_step, last)) if (last_step - step).abs() == 1 && note.staff.unwrap().0 == last.staff.unwrap().0 => side * advance(head(last)),
+			_ => 0
+		};
+		heads.push((dx, step, note));
+	}
+	heads
+}
+
+// Stacks accidentals from top to bottom on the staff of their note, each in the nearest column left of the heads where it does not collide with another accidental
+fn accidentals(heads: &[(i32, i8, &Note)]) -> Vec<(usize, usize, i8, char)> {
+	let mut accidentals = heads.iter().filter_map(|&(_, step, note)| note.accidental.map(|a| (usize::from(&note.staff.unwrap()), step, accidental::from(a)))).collect::<Vec<_>>();
+	accidentals.sort_by_key(|&(staff, step, _)| (std::cmp::Reverse(staff), -step));
+	let mut columns = Vec::<Vec<(usize, i8)>>::new();
+	accidentals.into_iter().map(|(staff, step, glyph)| {
+		let column = if let Some(column) = columns.iter().position(|column| column.iter().all(|&(other_staff, other)| other_staff != staff || (other - step).abs() >= 6)) { column } else { columns.push(Vec::new()); columns.len()-1 };
+		columns[column].push((staff, step));
+		(column, staff, step, glyph)
+	}).collect()
+}
+
 impl MeasureLayoutContext<'_> { pub fn beam(&mut self, staves: &[Staff], beam: &[Vec<&Note>]) {
-	use crate::{music_xml::{NoteType, NoteData, NoteTypeValue, StemDirection}, font::{SMuFont, SMuFL::{Anchor, note_head, flag}}, staff::{Index, Chord}};
+	use crate::{font::{SMuFont, SMuFL::{Anchor, flag}}, staff::{Index, Chord}};
 	use {iter::Single, vector::MinMax, ::xy::xy, ui::graphic::{Rect, Parallelogram}};
 	let MinMax{min: bottom, max: top} = beam.iter().map(|chord| chord.bounds(staves)).reduce(MinMax::minmax).unwrap();
-	let direction = if top-4 > 4-bottom { StemDirection::Down } else { StemDirection::Up };
-	let stem_anchor = if let StemDirection::Down = direction { Anchor::StemDownNW } else { Anchor::StemUpSE };
-	let stem_anchor = self.sheet.face.anchor(note_head::black, stem_anchor);
-
-	let beam = beam.iter().scan(self.x, |x, chord| {
-		let stem = *x + stem_anchor.x as u32;
-		*x += self.space();
-		Some((stem, chord))
+	let direction = if staves[beam[0].staff()].polyphonic { beam[0][0].voice_stem_direction() } else if top-4 > 4-bottom { StemDirection::Down } else { StemDirection::Up };
+
+	let sheet = self.sheet;
+	let scale = sheet.note_scale(beam[0][0]); // Grace and cue notes
+	self.measure.scale = scale;
+	let scaled = |v: i32| (v as f32 * scale) as i32;
+	let advance = |glyph| scaled(sheet.advance(glyph));
+	let column_width = advance(accidental::sharp) + (self.space()/4) as i32;
+	let (mut x, mut t) = (self.x, self.t);
+	let beam = beam.iter().map(|chord| {
+		let directions = chord.stem_directions(direction);
+		let heads = heads(&advance, staves, chord, *directions.first().unwrap_or(&direction));
+		let accidentals = accidentals(&heads);
+		let left = heads.iter().map(|&(dx,_,_)| -dx).max().unwrap_or(0) + accidentals.iter().map(|&(column,..)| column as i32 + 1).max().unwrap_or(0) * column_width + self.arpeggio_width(chord);
+		let width = heads.iter().map(|&(dx,_,note)| dx + advance(head(note))).max().unwrap_or(0) as u32;
+		let head = sheet.position(xy{x: (x + left as u32) as i32, y: 0}, xy{x: 0, y: 0}, &chord[0].position).x.max(0) as u32; // Reserves space for arpeggios, accidentals and heads displaced left of the stem (or at the encoded measure offset)
+		let head = if chord[0].grace.is_some() { head } else { self.onset(t, head, width) }; // Aligned with the heads of the other voices at the same onset (grace notes precede it)
+		t += chord.iter().filter_map(|note| note.duration).max().unwrap_or(0);
+		x = head + self.space();
+		(head, chord, directions, heads, accidentals)
 	}).collect::<Vec<_>>();
 
 	// Heads
-	for &(x, chord) in beam.iter() {
-		for note in chord.iter() {
-			if let Note{staff: Some(staff), r#type: Some(NoteType{value}), content:NoteData::Pitch(pitch), ..} = note {
-				self.push_glyph_at_pitch(x, staves.index(&staff), &pitch, {use {NoteTypeValue::*, note_head::*}; match value { Breve=>breve, Whole=>whole, Half=>half, _=>black }});
-			} else { unreachable!() }
+	for (x, chord, _, heads, accidentals) in beam.iter() {
+		let x = *x as i32;
+		let staff = chord.staff();
+		for &(dx, step, note) in heads {
+			self.set_color(note.color);
+			self.push_glyph((x+dx) as u32, staves.index(&note.staff.unwrap()).index, step, 0, head(note));
+		}
+		let left = heads.iter().map(|&(dx,_,_)| dx).min().unwrap_or(0);
+		self.set_color(chord[0].color);
+		for &(column, staff, step, glyph) in accidentals {
+			self.push_glyph((x + left - (column as i32 + 1) * column_width) as u32, staff, step, 0, glyph);
 		}
+		// Ledger lines (shared by all chord members and displaced heads, uncoloured)
+		self.set_color(None);
+		let right = heads.iter().map(|&(dx,_,note)| dx + advance(head(note))).max().unwrap_or(0);
+		self.ledgers(staff, chord.bounds(staves), (x+left) as u32, (x+right) as u32);
 	}
 
-	let stem_thickness = self.sheet.engraving_defaults.stem_thickness;
-
-	if let &[(left, first), .., (right, last)] = beam.as_slice() { // Beam (fixme: >2)
-		let right = right + stem_thickness as u32;
-		self.measure.graphic.parallelograms.push(Parallelogram{
-			top_left: xy{x: left as i32, y: self.y(first.staff(), first.stem_step(staves, direction))},
-			bottom_right: xy{x: right as i32, y: self.y(last.staff(), last.stem_step(staves, direction))},
-			vertical_thickness: self.sheet.engraving_defaults.beam_thickness
-		});
-	}
+	let stem_thickness = scaled(self.sheet.engraving_defaults.stem_thickness as i32) as u32;
 
 	//float opacity = allTied(beam[0]) ? 1./2 : 1;
-	for (x, chord) in beam.iter() { // Stem
-		let x = x + stem_anchor.x as u32;
+	let stems = beam.iter().map(|&(x, chord, ref directions, ..)| directions.iter().map(|&direction| { // Stem
 		let staff = chord.staff();
+		let MinMax{min: bottom, max: top} = chord.bounds(staves);
 		let stem_step = chord.stem_step(staves, direction);
-		if let StemDirection::Down = direction { // Bottom Left
-			self.measure.graphic.rects.push(Rect{min: xy{x: x as i32, y: self.y(staff, top)+stem_anchor.y}, max: xy{x: x as i32 + stem_thickness as i32, y: self.y(staff, stem_step)}});
+		(direction, if let StemDirection::Down = direction { // Bottom Left
+			let stem_anchor = self.sheet.face.anchor(note_head::black, Anchor::StemDownNW).map(|&v| scaled(v));
+			let x = x + stem_anchor.x as u32;
+			Rect{min: xy{x: x as i32, y: self.y(staff, top)+stem_anchor.y}, max: xy{x: x as i32 + stem_thickness as i32, y: self.y(staff, stem_step)}}
 		} else { // Top Right
-			self.measure.graphic.rects.push(Rect{min: xy{x: x as i32 - stem_thickness as i32, y: self.y(staff, stem_step)}, max: xy{x: x as i32, y: self.y(staff, bottom)+stem_anchor.y}});
+			let stem_anchor = self.sheet.face.anchor(note_head::black, Anchor::StemUpSE).map(|&v| scaled(v));
+			let x = x + stem_anchor.x as u32;
+			Rect{min: xy{x: x as i32 - stem_thickness as i32, y: self.y(staff, stem_step)}, max: xy{x: x as i32, y: self.y(staff, bottom)+stem_anchor.y}}
+		})
+	}).collect::<Vec<_>>()).collect::<Vec<_>>();
+	for ((_, chord, ..), stems) in beam.iter().zip(&stems) {
+		self.set_color(chord[0].color);
+		self.measure.graphic.rects.extend(stems.iter().map(|&(_, stem)| stem));
+	}
+
+	if let (&[(_, first, ..), .., (_, last, ..)], &[ref first_stems, .., ref last_stems]) = (beam.as_slice(), stems.as_slice()) { // Beam (fixme: >2)
+		let direction = first_stems.first().map(|&(direction,_)| direction).unwrap_or(direction);
+		if let (Some((_, left)), Some((_, right))) = (first_stems.iter().find(|(d,_)| d == &direction), last_stems.iter().find(|(d,_)| d == &direction)) {
+			self.set_color(first[0].color);
+			self.measure.graphic.parallelograms.push(Parallelogram{
+				top_left: xy{x: left.min.x, y: self.y(first.staff(), first.stem_step(staves, direction))},
+				bottom_right: xy{x: right.max.x, y: self.y(last.staff(), last.stem_step(staves, direction))},
+				vertical_thickness: scaled(self.sheet.engraving_defaults.beam_thickness as i32) as u32
+			});
+		}
+	}
+
+	// Acciaccatura slash through the first stem
+	if let (Some((_, chord, ..)), Some(&[(direction, stem), ..])) = (beam.first(), stems.first().map(|stems| stems.as_slice())) {
+		if let Some(Grace{slash: Some(true), ..}) = chord[0].grace {
+			self.set_color(chord[0].color);
+			let space = self.space() as i32;
+			let y = if let StemDirection::Down = direction { stem.max.y - space } else { stem.min.y + space };
+			self.measure.graphic.parallelograms.push(Parallelogram{
+				top_left: xy{x: stem.min.x - space/2, y: y + space/2},
+				bottom_right: xy{x: stem.max.x + space/2, y: y - space/2},
+				vertical_thickness: stem_thickness
+			});
 		}
 	}
 
 	// Flag
-	if let Some(&(x, chord)) = beam.iter().single() {
-		let stem_step = chord.stem_step(staves, direction);
-		let staff = chord.staff();
-		let value = if let StemDirection::Down = direction { chord.first() } else { chord.last() };
-		let flag = if let StemDirection::Down = direction { flag::down } else { flag::up };
-		let flag_anchor = if let StemDirection::Down = direction { Anchor::StemDownSW } else { Anchor::StemUpNW };
-		let value = &value.unwrap().r#type.as_ref().unwrap().value;
-		if value <= &NoteTypeValue::Eighth {
-			let xy{x, y: dy} = xy{x: x as i32, y: 0} + self.sheet.face.anchor(flag, flag_anchor);
-			self.push_glyph(x as u32, staff, stem_step, dy, flag::from(flag, NoteTypeValue::Eighth as u32 - *value as u32));
+	if let (Some(&(_, chord, ..)), Some(stems)) = (beam.iter().single(), stems.iter().single()) {
+		self.set_color(chord[0].color);
+		for &(direction, stem) in stems {
+			let stem_step = chord.stem_step(staves, direction);
+			let staff = chord.staff();
+			let flag = if let StemDirection::Down = direction { flag::down } else { flag::up };
+			let flag_anchor = if let StemDirection::Down = direction { Anchor::StemDownSW } else { Anchor::StemUpNW };
+			if let Some(NoteType{value}) = chord.first().unwrap().r#type.as_ref() {
+				if value <= &NoteTypeValue::Eighth {
+					let xy{x, y: dy} = xy{x: stem.min.x, y: 0} + self.sheet.face.anchor(flag, flag_anchor).map(|&v| scaled(v));
+					self.push_glyph(x as u32, staff, stem_step, dy, flag::from(flag, NoteTypeValue::Eighth as u32 - *value as u32));
+				}
+			}
 		}
 	}
+
+	// Articulations, fingerings, ornaments, notations and tuplets
+	self.beams += 1;
+	let beam_id = (beam.len() > 1).then(|| self.beams);
+	for ((x, chord, _, heads, accidentals), stems) in beam.iter().zip(&stems) {
+		self.set_color(chord[0].color);
+		let x = *x as i32;
+		let left = heads.iter().map(|&(dx,_,_)| x+dx).min().unwrap_or(x);
+		let right = heads.iter().map(|&(dx,_,note)| x + dx + advance(head(note))).max().unwrap_or(x);
+		let steps = stems.iter().map(|&(direction,_)| chord.stem_step(staves, direction)).map(|e| MinMax{min: e, max: e}).fold(chord.bounds(staves), MinMax::minmax);
+		let stem_up = stems.first().map_or(true, |&(direction,_)| direction == StemDirection::Up);
+		let edges = self.articulations(chord.staff(), chord, chord.bounds(staves), MinMax{min: steps.min, max: steps.max}, MinMax{min: left, max: right}, stems.first().map(|&(direction,_)| direction));
+		let heads = heads.iter().map(|&(dx, step, note)| (x+dx, step, note)).collect::<Vec<_>>();
+		let edges = self.fingerings(chord.staff(), &heads, edges, MinMax{min: left, max: right}, stems.first().map(|&(direction,_)| direction));
+		let tremolo = stems.first().map(|&(direction, stem)| ((stem.min.x+stem.max.x)/2, (chord.stem_step(staves, direction) + if let StemDirection::Down = direction { chord.bounds(staves).max } else { chord.bounds(staves).min }) / 2)).unwrap_or(((left+right)/2, chord.bounds(staves).max + 3));
+		let edges = self.ornaments(chord.staff(), chord, edges, MinMax{min: left, max: right}, tremolo);
+		self.notations(chord.staff(), chord, &heads, edges, MinMax{min: left, max: right}, left - accidentals.iter().map(|&(column,..)| column as i32 + 1).max().unwrap_or(0) * column_width);
+		self.tuplet(chord, chord.staff(), MinMax{min: left, max: right}, steps, stem_up, beam_id);
+	}
+
+	self.measure.scale = 1.;
+	self.set_color(None);
+	if beam[0].1[0].grace.is_some() { let space = self.space(); self.advance(space / 2); } // Principal note follows its grace notes
 }}
@@ -0,0 +1,53 @@
+use {::xy::{xy, size}, ui::{Graphic, widget::Target}, crate::{music_xml::Color, measure::Colored}};
+
+// Moves the coloured elements out of the graphic, into one graphic per colour
+fn split(mut graphic: Graphic, colors: &[Colored]) -> (Graphic, Vec<(Color, Graphic)>) {
+	let key = |&Color{a, r, g, b}: &Color| [a, r, g, b];
+	let mut colored = Vec::<(Color, Graphic)>::new();
+	let slots = colors.iter().map(|Colored{color, ..}| colored.iter().position(|(other, _)| key(other) == key(color)).unwrap_or_else(|| { colored.push((*color, Graphic::new(graphic.scale))); colored.len()-1 })).collect::<Vec<_>>();
+	let slot = |range: fn(&Colored) -> &std::ops::Range<usize>, index: usize| colors.iter().zip(&slots).rev().find(|(colored, _)| range(colored).contains(&index)).map(|(_, &slot)| slot); // Last colour set
+	let mut plain = Graphic::new(graphic.scale);
+	for (index, rect) in graphic.rects.drain(..).enumerate() { if let Some(slot) = slot(|c| &c.rects, index) { colored[slot].1.rects.push(rect) } else { plain.rects.push(rect) } }
+	for (index, parallelogram) in graphic.parallelograms.drain(..).enumerate() { if let Some(slot) = slot(|c| &c.parallelograms, index) { colored[slot].1.parallelograms.push(parallelogram) } else { plain.parallelograms.push(parallelogram) } }
+	for (index, glyph) in graphic.glyphs.drain(..).enumerate() { if let Some(slot) = slot(|c| &c.glyphs, index) { colored[slot].1.glyphs.push(glyph) } else { plain.glyphs.push(glyph) } }
+	(plain, colored)
+}
+
+fn paint(target: &mut Target, graphic: Graphic) -> ui::Result {
+	let graphic = std::cell::Cell::new(Some(graphic));
+	ui::widget::Widget::paint(&mut ui::graphic::Widget(|_| Ok(graphic.take().unwrap())), target)
+}
+fn channels(pixel: u32) -> [f32; 3] { [pixel >> 16, pixel >> 8, pixel].map(|channel| (channel & 0xFF) as f32 / 255.) }
+fn gray(pixel: u32) -> f32 { channels(pixel).iter().sum::<f32>() / 3. }
+// Pixels of a graphic painted alone
+fn render(size: size, graphic: Graphic) -> ui::Result<Vec<u32>> {
+	let mut data = vec![0; (size.x * size.y) as usize];
+	paint(&mut Target::new(size, &mut data[..]), graphic)?;
+	Ok(data)
+}
+
+// Paints the elements without colour, then composites each colour with the ink coverage of its elements rasterised alone
+// (as the difference from the background rasterised alone, relative to the farthest ink from this background)
+pub struct Widget<T: Fn(size) -> ui::Result<(Graphic, Vec<Colored>)>>(pub T);
+impl<T: Fn(size) -> ui::Result<(Graphic, Vec<Colored>)>> ui::widget::Widget for Widget<T> {
+	fn paint(&mut self, target: &mut Target) -> ui::Result {
+		let size = target.size;
+		let (graphic, colors) = (self.0)(size)?;
+		let scale = graphic.scale;
+		let (plain, colored) = split(graphic, &colors);
+		paint(target, plain)?;
+		if colored.is_empty() { return Ok(()); }
+		let background = render(size, Graphic::new(scale))?;
+		for (Color{a, r: red, g: green, b: blue}, graphic) in colored {
+			for (index, (&ink, &background)) in render(size, graphic)?.iter().zip(&background).enumerate() {
+				let coverage = ((gray(ink) - gray(background)).abs() / gray(background).max(1. - gray(background))).min(1.) * a as f32 / 255.;
+				if coverage == 0. { continue; }
+				let pixel = &mut target[xy{x: index as u32 % size.x, y: index as u32 / size.x}];
+				let under = channels(*pixel);
+				let [r, g, b] = [0, 1, 2].map(|channel| ((under[channel] + coverage * ([red, green, blue][channel] as f32 / 255. - under[channel])) * 255.) as u32);
+				*pixel = (*pixel & 0xFF000000) | r << 16 | g << 8 | b; // Keeps alpha
+			}
+		}
+		Ok(())
+	}
+}
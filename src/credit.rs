@@ -0,0 +1,23 @@
+use {::xy::xy, crate::{music_xml::{FormattedText, PrintStyleAlign, PrintStyle, Position, Work, Identification, Creator, Justify, VAlign}, measure::Measure}};
+
+impl Measure<'_> {
+	// Credit words at their position in tenths from the bottom left of the page, one line under the other
+	pub fn credit(&mut self, page_height: i32, FormattedText{justify, print_style_align: PrintStyleAlign{print_style: PrintStyle{position: Position{default_x, default_y, relative_x, relative_y}, font, ..}, valign}, content}: &FormattedText) {
+		let face = self.sheet.text_face(font);
+		let size = self.sheet.points(font.font_size.unwrap_or(12.));
+		let x = self.sheet.tenths(default_x.unwrap_or(0.) + relative_x.unwrap_or(0.));
+		let y = page_height - self.sheet.tenths(default_y.unwrap_or(0.) + relative_y.unwrap_or(0.));
+		for (index, line) in content.lines().enumerate() { self.push_aligned_text(face, size, xy{x, y: y + index as i32 * (size*6/5) as i32}, *justify, *valign, line); }
+	}
+	// Title centered from the top, composer right and lyricist left under it, when there are no credits. Returns the bottom
+	pub fn headers(&mut self, face: &'static ui::font::File<'static>, work: Option<&Work>, Identification{creator, ..}: &Identification, left: i32, right: i32, top: i32) -> i32 {
+		let creator = |r#type: &str| creator.iter().find(|creator| creator.r#type == r#type).map(|Creator{creator, ..}| creator.as_str());
+		let mut y = top;
+		if let Some(title) = work.and_then(|work| work.work_title.as_deref()) { y = self.push_aligned_text(face, self.sheet.points(24.), xy{x: (left+right)/2, y}, Some(Justify::Center), Some(VAlign::Top), title).max.y; }
+		let size = self.sheet.points(12.);
+		let mut bottom = y;
+		if let Some(composer) = creator("composer") { bottom = std::cmp::max(bottom, self.push_aligned_text(face, size, xy{x: right, y}, Some(Justify::Right), Some(VAlign::Top), composer).max.y); }
+		if let Some(lyricist) = creator("lyricist").or_else(|| creator("poet")) { bottom = std::cmp::max(bottom, self.push_aligned_text(face, size, xy{x: left, y}, Some(Justify::Left), Some(VAlign::Top), lyricist).max.y); }
+		bottom
+	}
+}
@@ -1,37 +1,121 @@
-use {xy::xy, fehler::throws, crate::Error,
-	crate::music_xml::{Font, PrintStyle, Direction, DirectionType, DirectionTypeData, Dynamics, Metronome, Wedge, OctaveShift}, 
-	crate::{measure::MeasureLayoutContext, staff::Staff}};
-impl MeasureLayoutContext<'_> { 
-	#[throws] pub fn direction(&mut self, _staves: &mut [Staff], Direction{direction_type, ..}: &Direction) {
+use {fehler::throws, ::xy::{xy, Rect}, crate::Error,
+	crate::music_xml::{Direction, DirectionType, DirectionTypeData, Dynamics, DynamicText, Metronome, MetronomeData, FormattedText, PrintStyleAlign, PrintStyle, Position, Wedge, WedgeType, OctaveShift, UpDownStopContinue, Pedal, PedalType, Placement},
+	crate::{font::SMuFL::{dynamic, note_head, metronome, repeat, pedal}, spanner::{Event, Point, Kind}, measure::MeasureLayoutContext, staff::Staff}};
+
+fn dynamic(text: DynamicText) -> char {
+	use {DynamicText::*, dynamic::from};
+	match text {
+		pppppp => dynamic::pppppp, ppppp => from(dynamic::pppppp, 1), pppp => from(dynamic::pppppp, 2), ppp => from(dynamic::pppppp, 3), pp => from(dynamic::pppppp, 4), p => dynamic::piano,
+		mp => dynamic::mp, mf => dynamic::mf, f => dynamic::forte, ff => dynamic::ff, fff => from(dynamic::ff, 1), ffff => from(dynamic::ff, 2), fffff => from(dynamic::ff, 3), ffffff => from(dynamic::ff, 4),
+		sf => dynamic::sf, sfp => dynamic::sfp, sfpp => dynamic::sfpp, fp => dynamic::fp, rf => dynamic::rf, rfz => dynamic::rfz, sfz => dynamic::sfz, sffz => dynamic::sffz, fz => dynamic::fz, n => dynamic::niente, pf => dynamic::pf, sfzp => dynamic::sfzp,
+	}
+}
+
+impl MeasureLayoutContext<'_> {
+	// Beat units as metronome note glyphs, equated to a tempo or to another beat unit (metric modulation). Returns the right
+	fn metronome(&mut self, xy{mut x, y}: xy<i32>, staff: usize, content: &[MetronomeData], parentheses: bool) -> i32 {
+		let (size, space) = (self.sheet.staff_height*2/3, self.space() as i32);
+		if parentheses { x = self.push_text(x, y, size, "("); }
+		self.measure.scale = 3./4.; // Text size
+		for (index, item) in content.iter().enumerate() {
+			let glyph = match item {
+				&MetronomeData::BeatUnit(value) => {
+					if index > 0 { x = self.push_text(x + space/4, y, size, "=") + space/4; }
+					metronome::from(value)
+				},
+				MetronomeData::BeatUnitDot => { x += space/4; metronome::augmentation_dot },
+				MetronomeData::PerMinute(per_minute) => { x = self.push_text(x + space/4, y, size, &format!("= {per_minute}")); continue; }
+			};
+			let bbox = self.sheet.bbox(glyph);
+			let dy = y - self.y(staff, 0) + (bbox.y_min as f32 * self.measure.scale) as i32; // Sits on the text baseline
+			self.push_glyph(x as u32, staff, 0, dy, glyph);
+			x += (self.sheet.advance(glyph) as f32 * self.measure.scale) as i32;
+		}
+		self.measure.scale = 1.;
+		if parentheses { x = self.push_text(x, y, size, ")"); }
+		x
+	}
+	// Glyph with its baseline at the computed position, or at the encoded one. Returns the right
+	fn push_positioned(&mut self, computed: xy<i32>, staff: usize, position: &Position, glyph: char) -> i32 {
+		let xy{x, y} = self.sheet.position(computed, xy{x: self.x as i32, y: self.y(staff, 8)}, position);
+		let dy = y - self.y(staff, 0);
+		self.push_glyph(std::cmp::max(0, x) as u32, staff, 0, dy, glyph);
+		x + self.sheet.advance(glyph)
+	}
+	// Text in the requested font, justified on x and aligned on the step baseline, or at its encoded position. Returns its bounds
+	fn words(&mut self, x: i32, staff: usize, step: i8, text: &FormattedText) -> Rect {
+		let FormattedText{justify, print_style_align: PrintStyleAlign{print_style: PrintStyle{position, font, ..}, valign}, content} = text;
+		let face = self.sheet.text_face(font);
+		let size = self.sheet.points(font.font_size.unwrap_or(12.));
+		let at = self.sheet.position(xy{x, y: self.y(staff, step)}, xy{x: self.x as i32, y: self.y(staff, 8)}, position);
+		self.push_aligned_text(face, size, at, *justify, *valign, content)
+	}
+	#[throws] pub fn direction(&mut self, staves: &mut [Staff], Direction{direction_type, staff, placement, ..}: &Direction) {
+		let staff = staff.as_ref().map_or(staves.len()-1, usize::from);
+		let step = if let Some(Placement::Below) = placement { -6 } else { 13 }; // Text baseline
+		let mut x = self.x as i32; // Successive direction types follow each other (tempo text then metronome mark)
+		let origin = xy{x, y: self.y(staff, 8)}; // Of encoded positions
 		for DirectionType{content} in direction_type {
 			for direction in content { match direction {
-				DirectionTypeData::Dynamics(Dynamics{text, print_style: PrintStyle{font: Font{font_family: Some(face), ..}, ..}}) => {
-					let text = format!("{text:?}");
-					let font = self.measure.sheet.font;
-					let face = if let Some((_,face)) = font.iter().find(|(key,_)| key==face) { face } else {
-						/*use std::{path::Path,env::var}
-						font.push((face.clone(), ui::font::open(Path::new(&(var("HOME")?+"/.local/share/fonts/BravuraText.otf")))?));
-						&font.iter().find(|(key,_)| key==face).unwrap().1*/
-						ui::text::default_font()[0] // TODO: italic
-					};
-					use ui::{graphic, text::{Plain, View, layout, Glyph, unicode_segmentation::UnicodeSegmentation}};
-					let text = View::new_with_face(&face, Plain(text));
-					for Glyph{x: dx, id, ..} in layout(&text.font, text.data.0.graphemes(true).enumerate()) {
-						let scale = num::Ratio{num: 1, div: 3};
-						self.measure.graphic.glyphs.push(graphic::Glyph{top_left: xy{
-							x: (self.x+dx) as i32 + face.glyph_hor_side_bearing(id).unwrap() as i32,
-							y: -((self.sheet.staff_height+self.staff_distance/2) as i32),
-						}, face, id, scale: scale.into()});
-					}
+				&DirectionTypeData::Dynamics(Dynamics{text, ref print_style}) => { // Centered under the note (or above per placement)
+					let glyph = dynamic(text);
+					let x = self.x as i32 + (self.sheet.advance(note_head::black) - self.sheet.advance(glyph)) / 2;
+					let step = if let Some(Placement::Above) = placement { 12 } else { -4 };
+					self.push_positioned(xy{x, y: self.y(staff, step) + self.sheet.center(glyph)}, staff, &print_style.position, glyph);
 				},
-				DirectionTypeData::Metronome(Metronome{..}) => {
-
+				DirectionTypeData::Words(words) => {
+					x = self.words(x, staff, step, words).max.x + self.space() as i32/2;
+				},
+				DirectionTypeData::Rehearsal(rehearsal) => { // Boxed
+					let bounds = self.words(x, staff, step + 4, rehearsal);
+					x = self.push_box(bounds).max.x + self.space() as i32/2;
+				},
+				DirectionTypeData::Segno(print_style_align)|DirectionTypeData::Coda(print_style_align) => {
+					let glyph = if let DirectionTypeData::Segno(_) = direction { repeat::segno } else { repeat::coda };
+					x = self.push_positioned(xy{x, y: self.y(staff, step + 2) + self.sheet.center(glyph)}, staff, &print_style_align.print_style.position, glyph) + self.space() as i32/2;
 				},
-				DirectionTypeData::Wedge(Wedge{..}) => {
+				DirectionTypeData::Metronome(Metronome{content, parentheses, print_style}) => {
+					let at = self.sheet.position(xy{x, y: self.y(staff, step)}, origin, &print_style.position);
+					x = self.metronome(at, staff, content, parentheses == &Some(true)) + self.space() as i32/2;
 				},
-				DirectionTypeData::OctaveShift(OctaveShift{..}) => {
+				&DirectionTypeData::Wedge(Wedge{r#type, number, spread, niente, ref position, color}) => { // Hairpin spanner (stop carries a diminuendo niente)
+					let kind = Kind::Wedge{crescendo: matches!(r#type, WedgeType::Crescendo), spread: self.sheet.tenths(spread.unwrap_or(15.)), niente: niente == Some(true), color};
+					let computed = xy{x: self.x as i32, y: self.y(staff, if let Some(Placement::Above) = placement { 12 } else { -4 })};
+					let xy{x, y} = position.as_ref().map_or(computed, |position| self.sheet.position(computed, origin, position));
+					let point = Point{kind, number, staff, step: self.sheet.step(staff, y), x};
+					match r#type { WedgeType::Crescendo|WedgeType::Diminuendo => self.spanners.push(Event::Start(point)), WedgeType::Stop => self.spanners.push(Event::Stop(point)), WedgeType::Continue => {} }
+				},
+				&DirectionTypeData::Pedal(Pedal{r#type, number, line, sign, ref print_style_align}) => { // Below the bottom staff: bracket line and/or Ped. and release signs
+					let xy{x, y} = self.sheet.position(xy{x: self.x as i32, y: self.y(0, -6)}, xy{x: self.x as i32, y: self.y(0, 8)}, &print_style_align.print_style.position);
+					let step = self.sheet.step(0, y);
+					let point = |notch| Point{kind: Kind::Pedal{notch}, number, staff: 0, step, x};
+					if line == Some(true) { match r#type {
+						PedalType::Start|PedalType::Sostenuto|PedalType::Resume => self.spanners.push(Event::Start(point(false))),
+						PedalType::Stop|PedalType::Discontinue => self.spanners.push(Event::Stop(point(false))),
+						PedalType::Change => self.spanners.extend([Event::Stop(point(true)), Event::Start(point(true))]),
+						PedalType::Continue => {}
+					}}
+					if sign.unwrap_or(line != Some(true)) {
+						let glyphs = match r#type { PedalType::Start|PedalType::Sostenuto|PedalType::Resume => &[pedal::ped][..], PedalType::Stop => &[pedal::up], PedalType::Change => &[pedal::up, pedal::ped], _ => &[] };
+						let mut x = x;
+						for &glyph in glyphs {
+							self.push_glyph(x as u32, 0, step, self.sheet.center(glyph), glyph);
+							x += self.sheet.advance(glyph) + self.space() as i32/4;
+						}
+					}
+				},
+				&DirectionTypeData::OctaveShift(OctaveShift{r#type, number, size, ref print_style}) => { // Displaces the staff notes until the stop, under a bracket
+					let shift = ((size - 1) / 7) as i8;
+					let above = matches!(r#type, UpDownStopContinue::Down); // Notes are written down for an 8va
+					let xy{x, y} = self.sheet.position(xy{x: self.x as i32, y: self.y(staff, if above { 13 } else { -5 })}, origin, &print_style.position);
+					let point = Point{kind: Kind::OctaveShift{size, above}, number, staff, step: self.sheet.step(staff, y), x};
+					match r#type {
+						UpDownStopContinue::Down => { staves[staff].octave = shift; self.spanners.push(Event::Start(point)); },
+						UpDownStopContinue::Up => { staves[staff].octave = -shift; self.spanners.push(Event::Start(point)); },
+						UpDownStopContinue::Stop => { staves[staff].octave = 0; self.spanners.push(Event::Stop(point)); },
+						UpDownStopContinue::Continue => {}
+					}
 				},
-				_ => panic!("{direction:?}")
 			}}	
 		}
 	}
@@ -0,0 +1,26 @@
+use {vector::MinMax, crate::{music_xml::{Note, Notation, Technical, TechnicalData, Fingering, Placement, StemDirection}, font::SMuFL::fingering, measure::MeasureLayoutContext}};
+impl MeasureLayoutContext<'_> {
+	// Stacks fingerings beyond the articulations on the side opposite the stem (or per placement), the fingering of the farthest head first (nearest the chord) so fingerings read in the order of the heads
+	// Substitutions follow the replaced finger on the same row, alternates are parenthesized. Returns the outer edges
+	pub fn fingerings(&mut self, staff: usize, heads: &[(i32, i8, &Note)], edges: MinMax<i8>, MinMax{min: left, max: right}: MinMax<i32>, direction: Option<StemDirection>) -> MinMax<i8> {
+		let (mut above, mut below) = (edges.max, edges.min);
+		let mut heads = heads.to_vec();
+		heads.sort_by_key(|&(_, step, _)| step);
+		for (above_side, heads) in [(true, heads.clone()), (false, heads.into_iter().rev().collect())] {
+			for (_, _, note) in heads {
+				let mut rows = Vec::<Vec<char>>::new();
+				for &Fingering{finger, substitution, alternate, placement, ..} in note.notations().filter_map(|notation| if let Notation::Technical(Technical{content}) = notation { Some(content) } else { None }).flatten().map(|TechnicalData::Fingering(fingering)| fingering) {
+					if above_side != match placement { Some(Placement::Above) => true, Some(Placement::Below) => false, None => !matches!(direction, Some(StemDirection::Up)) } { continue; }
+					let digits = finger.to_string().chars().map(fingering::from).collect::<Vec<_>>();
+					match rows.last_mut() {
+						Some(row) if substitution == Some(true) => { row.push(if above_side { fingering::substitution_above } else { fingering::substitution_below }); row.extend(digits); }
+						_ if alternate == Some(true) => rows.push(std::iter::once(fingering::left_parenthesis).chain(digits).chain(std::iter::once(fingering::right_parenthesis)).collect()),
+						_ => rows.push(digits),
+					}
+				}
+				for row in rows { self.push_stacked((left+right)/2, staff, if above_side { &mut above } else { &mut below }, above_side, &row, 1.); }
+			}
+		}
+		MinMax{min: below, max: above}
+	}
+}
@@ -1,10 +1,57 @@
 #[allow(non_snake_case)] pub mod SMuFL {
     #![allow(non_upper_case_globals)]
-    pub struct EngravingDefaults {pub staff_line_thickness: u32, pub stem_thickness: u32, pub thin_barline_thickness: u32, pub beam_thickness: u32}
+    pub struct EngravingDefaults {pub staff_line_thickness: u32, pub stem_thickness: u32, pub thin_barline_thickness: u32, pub thick_barline_thickness: u32, pub barline_separation: u32, pub thin_thick_barline_separation: u32, pub repeat_barline_dot_separation: u32, pub repeat_ending_line_thickness: u32, pub hairpin_thickness: u32, pub octave_line_thickness: u32, pub pedal_line_thickness: u32, pub beam_thickness: u32, pub leger_line_thickness: u32, pub leger_line_extension: u32, pub tuplet_bracket_thickness: u32}
     #[derive(PartialEq)] pub enum Anchor { StemUpNW, StemDownNW, StemUpSE, StemDownSW }
     pub mod clef {
 	    pub const G : char = '\u{E050}';
 	    pub const F : char = '\u{E062}';
+	    pub const C : char = '\u{E05C}';
+    }
+    pub mod dynamic {
+	    pub const piano : char = '\u{E520}';
+	    pub const forte : char = '\u{E522}';
+	    pub const niente : char = '\u{E526}';
+	    pub const niente_for_hairpin : char = '\u{E541}';
+	    pub const pppppp : char = '\u{E527}';
+	    pub const mp : char = '\u{E52C}';
+	    pub const mf : char = '\u{E52D}';
+	    pub const pf : char = '\u{E52E}';
+	    pub const ff : char = '\u{E52F}';
+	    pub const fp : char = '\u{E534}';
+	    pub const fz : char = '\u{E535}';
+	    pub const sf : char = '\u{E536}';
+	    pub const sfp : char = '\u{E537}';
+	    pub const sfpp : char = '\u{E538}';
+	    pub const sfz : char = '\u{E539}';
+	    pub const sfzp : char = '\u{E53A}';
+	    pub const sffz : char = '\u{E53B}';
+	    pub const rf : char = '\u{E53C}';
+	    pub const rfz : char = '\u{E53D}';
+	    pub fn from(first: char, offset: u32) -> char { u32::try_into(u32::from(first)+offset).unwrap() }
+    }
+    pub mod metronome {
+	    pub const double_whole : char = '\u{ECA0}';
+	    pub const whole : char = '\u{ECA2}';
+	    pub const half_up : char = '\u{ECA3}';
+	    pub const augmentation_dot : char = '\u{ECB7}';
+	    pub fn from(value: crate::music_xml::NoteTypeValue) -> char { use crate::music_xml::NoteTypeValue::*; match value { Half|Quarter|Eighth|_16th|_32th|_64th|_128th|_256th|_512th|_1024th => u32::try_into(u32::from(half_up)+(Half as u32 - value as u32)*2).unwrap(), Whole => whole, Breve|Long|Maxima => double_whole } }
+    }
+    pub mod octave { // Numeral, Alta, Bassa
+	    pub const eight : char = '\u{E510}';
+	    pub const fifteen : char = '\u{E514}';
+	    pub const twenty_two : char = '\u{E517}';
+	    pub fn from(size: u8) -> char { match size { 15 => fifteen, 22 => twenty_two, _ => eight } }
+	    pub fn alta(numeral: char) -> char { u32::try_into(u32::from(numeral)+1).unwrap() }
+	    pub fn bassa(numeral: char) -> char { u32::try_into(u32::from(numeral)+2).unwrap() }
+    }
+    pub mod pedal {
+	    pub const ped : char = '\u{E650}';
+	    pub const up : char = '\u{E655}';
+    }
+    pub mod repeat {
+	    pub const dot : char = '\u{E044}';
+	    pub const segno : char = '\u{E047}';
+	    pub const coda : char = '\u{E048}';
     }
     pub mod note_head {
 	    pub const breve : char = '\u{E0A1}';
@@ -18,10 +65,85 @@
 	    pub const down : char = '\u{E241}';
 	    pub fn from(flag: char, value: u32) -> char { u32::try_into(u32::from(flag)+value*2).unwrap() }
     }
+    pub mod rest {
+	    pub const maxima : char = '\u{E4E0}';
+	    pub fn from(value: u32) -> char { u32::try_into(u32::from(maxima)+value).unwrap() }
+    }
+    pub mod articulation { // Above, Below = Above + 1
+	    pub const accent : char = '\u{E4A0}';
+	    pub const staccato : char = '\u{E4A2}';
+	    pub const tenuto : char = '\u{E4A4}';
+	    pub const staccatissimo : char = '\u{E4A6}';
+	    pub const staccatissimo_stroke : char = '\u{E4AA}';
+	    pub const marcato : char = '\u{E4AC}';
+	    pub const tenuto_staccato : char = '\u{E4B2}';
+	    pub const stress : char = '\u{E4B6}';
+	    pub const unstress : char = '\u{E4B8}';
+	    pub const soft_accent : char = '\u{ED40}';
+	    pub fn below(above: char) -> char { u32::try_into(u32::from(above)+1).unwrap() }
+	    pub const breath_mark : char = '\u{E4CE}';
+	    pub const caesura : char = '\u{E4D1}';
+    }
+    pub mod ornament {
+	    pub const trill : char = '\u{E566}';
+	    pub const turn : char = '\u{E567}';
+	    pub const inverted_turn : char = '\u{E568}';
+	    pub const short_trill : char = '\u{E56C}';
+	    pub const mordent : char = '\u{E56D}';
+	    pub const schleifer : char = '\u{E587}';
+	    pub const wiggle_trill : char = '\u{EAA4}';
+    }
+    pub mod fermata { // Above, Below = Above + 1
+	    pub const normal : char = '\u{E4C0}';
+	    pub const very_short : char = '\u{E4C2}';
+	    pub const short : char = '\u{E4C4}';
+	    pub const long : char = '\u{E4C6}';
+	    pub const very_long : char = '\u{E4C8}';
+	    pub const long_henze : char = '\u{E4CA}';
+	    pub const short_henze : char = '\u{E4CC}';
+	    pub const curlew : char = '\u{E4D6}';
+	    pub fn below(above: char) -> char { u32::try_into(u32::from(above)+1).unwrap() }
+    }
+    pub mod arpeggio {
+	    pub const up : char = '\u{E634}';
+	    pub const down : char = '\u{E635}';
+	    pub const segment : char = '\u{E63C}';
+    }
+    pub mod glissando {
+	    pub const wiggle : char = '\u{EAAF}';
+    }
+    pub mod tremolo {
+	    pub const one : char = '\u{E220}';
+	    pub const buzz_roll : char = '\u{E22A}';
+	    pub fn from(marks: u8) -> char { u32::try_into(u32::from(one)+std::cmp::min(marks, 5) as u32-1).unwrap() } // Up to 5 (then fingered tremolos)
+    }
+    pub mod brass {
+	    pub const scoop : char = '\u{E5D0}';
+	    pub const doit : char = '\u{E5D5}';
+	    pub const falloff : char = '\u{E5D8}';
+	    pub const plop : char = '\u{E5E0}';
+    }
+    pub mod fingering {
+	    pub const zero : char = '\u{ED10}';
+	    pub const substitution_above : char = '\u{ED20}';
+	    pub const substitution_below : char = '\u{ED21}';
+	    pub const left_parenthesis : char = '\u{ED28}';
+	    pub const right_parenthesis : char = '\u{ED29}';
+	    pub const six : char = '\u{ED24}';
+	    pub fn from(digit: char) -> char { let digit = digit.to_digit(10).unwrap(); u32::try_into(if digit < 6 { u32::from(zero)+digit } else { u32::from(six)+digit-6 }).unwrap() } // 6-9 follow the letters
+    }
     pub mod accidental {
 	    pub const flat : char = '\u{E260}';
-	    //pub const natural : char = '\u{E261}';
+	    pub const natural : char = '\u{E261}';
 	    pub const sharp : char = '\u{E262}';
+	    pub const double_sharp : char = '\u{E263}';
+	    pub const double_flat : char = '\u{E264}';
+	    pub fn from(accidental: crate::music_xml::Accidental) -> char { use crate::music_xml::Accidental::*; match accidental { Flat=>flat, Natural=>natural, Sharp=>sharp } }
+    }
+    pub mod tuplet {
+	    pub const zero : char = '\u{E880}';
+	    pub const colon : char = '\u{E88A}';
+	    pub fn from(digit: char) -> char { u32::try_into(u32::from(zero)+digit.to_digit(10).unwrap()).unwrap() }
     }
     pub mod time_signature {
 	    pub const zero : char = '\u{E080}';
@@ -41,7 +163,7 @@ mod bravura {
 
 	impl SMuFont for ttf_parser::Face<'_> {
 		fn engraving_defaults() -> SMuFL::EngravingDefaults {
-			SMuFL::EngravingDefaults{staff_line_thickness: 32, stem_thickness: 30, thin_barline_thickness: 40, beam_thickness: 250}
+			SMuFL::EngravingDefaults{staff_line_thickness: 32, stem_thickness: 30, thin_barline_thickness: 40, thick_barline_thickness: 125, barline_separation: 100, thin_thick_barline_separation: 100, repeat_barline_dot_separation: 40, repeat_ending_line_thickness: 40, hairpin_thickness: 40, octave_line_thickness: 40, pedal_line_thickness: 40, beam_thickness: 250, leger_line_thickness: 40, leger_line_extension: 100, tuplet_bracket_thickness: 40}
 		}
 		fn anchor(&self, glyph: char, anchor: SMuFL::Anchor) -> int2 {
 			assert_eq!(self.units_per_em(), 1000);
@@ -0,0 +1,15 @@
+use {::xy::xy, crate::{music_xml::{Justify, VAlign}, measure::Measure}};
+
+impl Measure<'_> {
+	pub fn label_width(&self, label: &str) -> i32 { Self::text_width(ui::text::default_font()[0], self.sheet.points(12.), label) }
+	// Part name or abbreviation right aligned on x, centered on the staves
+	pub fn part_label(&mut self, staves: usize, x: i32, label: &str) {
+		let y = (self.sheet.y(staves-1, 8) + self.sheet.y(0, 0)) / 2;
+		self.push_aligned_text(ui::text::default_font()[0], self.sheet.points(12.), xy{x, y}, Some(Justify::Right), Some(VAlign::Middle), label);
+	}
+	// Above the top staff from x, optionally boxed
+	pub fn measure_number(&mut self, staves: usize, x: i32, number: u32, boxed: bool) {
+		let bounds = self.push_aligned_text(ui::text::default_font()[0], self.sheet.staff_height/2, xy{x, y: self.sheet.y(staves-1, 11)}, None, None, &number.to_string());
+		if boxed { self.push_box(bounds); }
+	}
+}
@@ -1,68 +1,279 @@
-use {::xy::{xy, size}, ui::{Ratio, Graphic, graphic::vertical}, crate::{music_xml::{self, MusicXML}, Font}};
-pub fn layout(font: Font, music: &MusicXML, size: size) -> Graphic {
-	use crate::{sheet::Sheet, staff::Staff, music::*, measure::{MeasureLayoutContext,MusicLayoutContext}};
-	let sheet = Sheet::new(font);
-	let scale = Ratio{num: 240, div: sheet.staff_height};
-	let output_size = size;
-	let size = size.map(|&x| scale.rcp().ceil(x));
+use {::xy::{xy, size, int2}, ui::{Ratio, Graphic, graphic::vertical}, crate::{music_xml::{self, MusicXML, ScorePartwise, Credit, PageLayout, PageMargins, SystemLayout, SystemMargins}, sheet::{Sheet, Options, MeasureNumbering}, measure::{Measure, Colored}, Font}};
+
+// Appends a graphic with its colour ranges at offset
+fn append(graphic: &mut Graphic, colors: &mut Vec<Colored>, mut source: Graphic, source_colors: Vec<Colored>, offset: int2) {
+	colors.extend(source_colors.into_iter().map(|Colored{color, rects, parallelograms, glyphs}| {
+		let shift = |range: std::ops::Range<usize>, base: usize| base+range.start..base+range.end;
+		Colored{color, rects: shift(rects, graphic.rects.len()), parallelograms: shift(parallelograms, graphic.parallelograms.len()), glyphs: shift(glyphs, graphic.glyphs.len())}
+	}));
+	graphic.rects.extend(source.rects.drain(..).map(|mut x| { x.translate(offset); x }));
+	graphic.parallelograms.extend(source.parallelograms.drain(..).map(|mut x| { x.translate(offset); x }));
+	graphic.glyphs.extend(source.glyphs.drain(..).map(|mut x| { x.translate(offset); x }));
+}
+
+// Whether the barline ending the previous measure is drawn before a measure starting with a left barline (not): unless plain, both are drawn (a backward repeat before a forward repeat)
+fn right_barline(right: Option<&music_xml::Barline>, left: bool) -> bool { !left || right.map_or(false, |right| right.repeat.is_some() || !matches!(right.bar_style, None|Some(music_xml::BarStyle::Regular))) }
+
+// Staves across the width in system coordinates (bottom line of the bottom staff at y=0), with the page break and layout requested by a print at its first measure
+struct System<'t> { graphic: Graphic, colors: Vec<Colored>, new_page: bool, layout: Option<&'t SystemLayout> }
+impl System<'_> {
+	fn new(sheet: &Sheet, staves: usize, indent: u32, width: u32, right: i32) -> Self {
+		let mut graphic = Graphic::new(Default::default());
+		graphic.rects.extend(sheet.raster(0..staves, indent as i32, width as i32));
+		graphic.rects.push(vertical(right, sheet.engraving_defaults.thin_barline_thickness, sheet.y(staves-1, 8), sheet.y(0, 0)));
+		Self{graphic, colors: Vec::new(), new_page: false, layout: None}
+	}
+}
+
+// Flows the measures of each part into systems of width, breaking where full or requested. right: x of the line closing the staves.
+// Systems are indented by the part names on the first system and by their abbreviations on the following ones
+fn systems<'t>(sheet: &Sheet, music: &'t MusicXML, width: u32, right_line: i32) -> Vec<System<'t>> {
+	use crate::{staff::Staff, music::*, measure::{MeasureLayoutContext,MusicLayoutContext}, spanner::{self, Open}};
+	fn extend(system: &mut System, mut measure: Measure, offset: int2) {
+		measure.set_color(None);
+		let Measure{graphic, colors, ..} = measure;
+		append(&mut system.graphic, &mut system.colors, graphic, colors, offset);
+	}
+	fn style(barline: Option<&music_xml::Barline>) -> (Option<music_xml::BarStyle>, Option<music_xml::BackwardForward>) { barline.map_or((None, None), |barline| (barline.bar_style, barline.repeat.as_ref().map(|repeat| repeat.direction))) }
+	fn barline(system: &mut System, sheet: &Sheet, staves: usize, barline: Option<&music_xml::Barline>, x: i32) { // Right aligned on x
+		let mut measure = Measure::new(sheet);
+		let (style, repeat) = style(barline);
+		let left = x + (sheet.engraving_defaults.thin_barline_thickness/2) as i32 - measure.barline_width(style, repeat);
+		measure.barline(staves, left, style, repeat);
+		extend(system, measure, xy{x: 0, y: 0});
+	}
+	// Lays out a measure, after the clefs and key reprinted at a system start (given its first attributes).
+	// Again until the x of each onset is stable, as later voices may move the heads of the first ones
+	fn layout_measure<'t>(sheet: &'t Sheet, staves: &mut [Staff], measure: &music_xml::Measure, system_start: Option<Option<&music_xml::Attributes>>) -> MeasureLayoutContext<'t> {
+		let mut onsets = Default::default();
+		loop {
+			let mut pass = staves.to_vec();
+			let measure = layout_pass(sheet, &mut pass, measure, system_start, onsets.clone());
+			if measure.onsets == onsets { staves.clone_from_slice(&pass); break measure; }
+			onsets = measure.onsets.clone();
+		}
+	}
+	fn layout_pass<'t>(sheet: &'t Sheet, staves: &mut [Staff], measure: &music_xml::Measure, system_start: Option<Option<&music_xml::Attributes>>, onsets: std::collections::BTreeMap<u32, u32>) -> MeasureLayoutContext<'t> {
+		let music_data = sort_by_start_time(&measure.music_data);
+		let music_data = batch_beamed_group_of_notes(music_data);
+		let mut measure = MusicLayoutContext{music_data, layout_context: MeasureLayoutContext::new(sheet)};
+		measure.onsets = onsets;
+		if let Some(leading) = system_start { measure.system_start(staves, leading); }
+		while let Some((_, _, music_data)) = measure.next() {
+			//eprintln!("{music_data:?}");
+			use {BeamedMusicData::{Beam, MusicData}, music_xml::MusicData::*};
+		    match music_data {
+			    Beam(beam) => measure.beam(staves, &beam),
+			    MusicData(music_data) => match music_data {
+				    Note(note) => measure.rest(staves, note),
+				    Backup(_) => {},
+				    Attributes(attributes) => measure.attributes(staves, attributes),
+					Direction(direction) => measure.direction(staves, direction).unwrap(),
+					Print(_) => {},
+					Barline(barline) => measure.barline(staves, barline),
+				    _ => {},
+			    }
+		    }
+		}
+		measure.layout_context
+	}
+	fn leading(measure: &music_xml::Measure) -> Option<&music_xml::Attributes> { // Changes at the start of a measure
+		measure.music_data.iter().take_while(|music_data| !matches!(music_data, music_xml::MusicData::Note(_)|music_xml::MusicData::Backup(_)|music_xml::MusicData::Forward(_))).find_map(|music_data| if let music_xml::MusicData::Attributes(attributes) = music_data { Some(attributes) } else { None })
+	}
+	fn measure_print(measure: &music_xml::Measure) -> Option<&music_xml::Print> { measure.music_data.iter().find_map(|music_data| if let music_xml::MusicData::Print(print) = music_data { Some(print) } else { None }) }
+	fn breaks(print: Option<&music_xml::Print>) -> bool { print.map_or(false, |print| print.new_page == Some(true) || print.new_system == Some(true)) } // Starts a new system
+	let score_parts = music.score_partwise.part_list.score_parts().collect::<Vec<_>>();
+	let label = |part: &music_xml::Part, first: bool| score_parts.iter().find(|score_part| score_part.id == part.id).and_then(|score_part| if first { Some(score_part.part_name.as_str()) } else { score_part.part_abbreviation.as_deref() }).filter(|label| !label.is_empty());
+	let space = sheet.staff_height/4;
+	let indents = [true, false].map(|first| music.score_partwise.parts.iter().filter_map(|part| label(part, first)).map(|label| Measure::new(sheet).label_width(label) as u32 + space).max().unwrap_or(0));
+	let indent = |index: usize| indents[std::cmp::min(index, 1)];
 	let mut staves = <[Staff; 2]>::default();
-	let mut graphic = Graphic::new(scale);
+	let mut systems = Vec::new();
 	for part in &music.score_partwise.parts {
-		let mut system = xy{x:0,y:0};
-		graphic.rects.extend(sheet.raster(staves.iter()));
-		graphic.rects.push(vertical(
-			scale.rcp()*(scale*((scale.rcp()*output_size.x - scale.rcp().ceil(scale.ceil(num::div_ceil(sheet.engraving_defaults.thin_barline_thickness,2)))))) as i32,
-			sheet.engraving_defaults.thin_barline_thickness,
-			system.y as i32+sheet.y(staves.len()-1, 8),
-			system.y as i32+sheet.y(0, 0)
-		));
-		for measure in &part.measures {
-			let music_data = sort_by_start_time(&measure.music_data);
-			let music_data = batch_beamed_group_of_notes(music_data);
-			let mut measure = MusicLayoutContext{music_data, layout_context: MeasureLayoutContext::new(&sheet)};
-			while let Some((_, _, music_data)) = measure.next() {
-				//eprintln!("{music_data:?}");
-				use {BeamedMusicData::{Beam, MusicData}, music_xml::MusicData::*};
-			    match music_data {
-				    Beam(beam) => measure.beam(&staves, &beam),
-				    MusicData(music_data) => match music_data {
-					    Backup(_) => {},
-					    Attributes(attributes) => measure.attributes(&mut staves, attributes),
-						Direction(direction) => measure.direction(&mut staves, direction).unwrap(),
-						Print(_) => {},
-					    _ => {},
-				    }
-			    }
+		let (mut index, mut x) = (0, indent(0)); // System, position in system
+		let mut spanners = Vec::<Open>::new(); // System coordinates
+		let mut right = None; // Barline ending the previous measure
+		if systems.is_empty() { systems.push(System::new(sheet, staves.len(), indent(0), width, right_line)); }
+		for (measure_index, source) in part.measures.iter().enumerate() {
+			let number = Some(source.number).filter(|&number| number > 0 && source.implicit != Some(true)); // Unnumbered pickup (or implicit) measures
+			let barlines = || source.music_data.iter().filter_map(|music_data| if let music_xml::MusicData::Barline(barline) = music_data { Some(barline) } else { None });
+			let left = barlines().any(|barline| matches!(barline.location, music_xml::RightLeftMiddle::Left) && (barline.bar_style.is_some() || barline.repeat.is_some()));
+			let print = measure_print(source);
+			let new_page = print.map_or(false, |print| print.new_page == Some(true));
+			let new_system = breaks(print);
+			for (index, staff) in staves.iter_mut().enumerate() { // Stems and rests are placed by voice when a staff has several voices
+				use itertools::Itertools;
+				staff.polyphonic = source.music_data.iter().filter_map(|music_data| if let music_xml::MusicData::Note(note@music_xml::Note{staff: Some(staff), ..}) = music_data { (usize::from(staff) == index).then(|| note.voice) } else { None }).unique().count() > 1;
 			}
-			let mut measure = measure.layout_context;
-			let space = measure.space();
-			if system.x + measure.x > size.x {
-				system.x = 0;
-				system.y += 2*sheet.staff_distance;
-				graphic.rects.extend(sheet.raster(staves.iter()).map(|mut x| { x.translate(xy{x:0, y: system.y as i32}); x }));
-				graphic.rects.push(vertical(
-					size.x as i32,
-					sheet.engraving_defaults.thin_barline_thickness,
-					system.y as i32+sheet.y(staves.len()-1, 8),
-					system.y as i32+sheet.y(0, 0)
-				));
-			} else {
-				measure.advance(space / 2);
+			let next = part.measures.get(measure_index+1).and_then(leading);
+			let leading = leading(source);
+			let (right_style, right_repeat) = style(barlines().find(|barline| matches!(barline.location, music_xml::RightLeftMiddle::Right)));
+			let reserve = |staves: &[Staff]| next.map_or(0, |next| { // Courtesy changes when the measure ends the system
+				let mut courtesy = MeasureLayoutContext::new(sheet);
+				courtesy.courtesy(&mut staves.to_vec(), next) + (courtesy.barline_width(right_style, right_repeat) as u32) + space/2
+			});
+			let fits = |x: u32, staves: &[Staff], measure: u32| x + measure <= width && { // Reserves the courtesy changes only when the following measure starts the next system
+				let reserve = reserve(staves);
+				reserve == 0 || x + measure + reserve <= width || part.measures.get(measure_index+1).map_or(true, |following| !breaks(measure_print(following)) && x + measure + space + layout_measure(sheet, &mut staves.to_vec(), following, None).x <= width)
 			};
-			graphic.rects.extend(measure.graphic.rects.drain(..).map(|mut x| { x.translate(system.signed()); x }));
-			graphic.parallelograms.extend(measure.graphic.parallelograms.drain(..).map(|mut x| { x.translate(system.signed()); x }));
-			graphic.glyphs.extend(measure.graphic.glyphs.drain(..).map(|mut x| { x.translate(system.signed()); x }));
-			if system.x > 0 {
-				graphic.rects.push(vertical(
-					(system.x - (space / 2)) as i32,
-					sheet.engraving_defaults.thin_barline_thickness,
-					system.y as i32+sheet.y(staves.len()-1, 8),
-					system.y as i32+sheet.y(0, 0)
-				));
+			let before = staves.clone();
+			let mut measure = layout_measure(sheet, &mut staves, source, None);
+			let mut broken = false;
+			while x > indent(index) && (!fits(x, &staves, measure.x) || new_system) { // Again after the measure is laid out at the system start
+				for open in spanners.iter_mut() { // Continues on next system
+					let mut measure = Measure::new(sheet);
+					measure.spanner(open, &spanner::Point{x: width as i32, ..open.start}, true);
+					extend(&mut systems[index], measure, xy{x: 0, y: 0});
+					*open = Open{start: spanner::Point{x: indent(index+1) as i32, ..open.start}, continued: true};
+				}
+				if let Some(leading) = leading { // Courtesy changes before the barline
+					let mut courtesy = MeasureLayoutContext::new(sheet);
+					let courtesy_width = courtesy.courtesy(&mut before.clone(), leading) as i32;
+					let (style, repeat) = style(right);
+					let courtesy_x = width as i32 - courtesy.barline_width(style, repeat) - (space/2) as i32 - courtesy_width;
+					extend(&mut systems[index], courtesy.measure, xy{x: courtesy_x, y: 0});
+				}
+				if right.is_some() { barline(&mut systems[index], sheet, staves.len(), right, width as i32); }
+				index += 1;
+				x = indent(index);
+				if index == systems.len() { systems.push(System::new(sheet, staves.len(), indent(index), width, right_line)); }
+				staves = before.clone(); // Lays out again after the reprinted clefs and key
+				measure = layout_measure(sheet, &mut staves, source, Some(leading));
+				broken = true;
+			}
+			if !broken { measure.end(space / 2); }
+			let start = x == indent(index);
+			if start { // Starts a system
+				let system = &mut systems[index];
+				system.new_page |= new_page;
+				if let Some(layout) = print.and_then(|print| print.system_layout.as_ref()) { system.layout = Some(layout); }
+				if let Some(label) = label(part, index == 0) {
+					let mut measure = Measure::new(sheet);
+					measure.part_label(staves.len(), (indent(index) - space/2) as i32, label);
+					extend(system, measure, xy{x: 0, y: 0});
+				}
+			}
+			if let Some(number) = number.filter(|number| match sheet.options.measure_numbering { MeasureNumbering::None => false, MeasureNumbering::System => start && measure_index > 0, MeasureNumbering::Every(every) => number.checked_rem(every) == Some(0) }) {
+				let mut measure = Measure::new(sheet);
+				measure.measure_number(staves.len(), x as i32, number, sheet.options.boxed_measure_numbers);
+				extend(&mut systems[index], measure, xy{x: 0, y: 0});
 			}
-			system.x += measure.x + (space / 2);
-			break;
+			for event in measure.spanners.drain(..) {
+				use spanner::Event::*;
+				match event {
+					Start(start) => spanners.push(Open{start: spanner::Point{x: x as i32 + start.x, ..start}, continued: false}),
+					Stop(stop) => if let Some(open) = spanners.iter().rposition(|open| open.matches(&stop)) {
+						let mut measure = Measure::new(sheet);
+						measure.spanner(&spanners.remove(open), &spanner::Point{x: x as i32 + stop.x, ..stop}, false);
+						extend(&mut systems[index], measure, xy{x: 0, y: 0});
+					}
+				}
+			}
+			extend(&mut systems[index], measure.measure, xy{x: x as i32, y: 0});
+			if x > indent(index) && right_barline(right, left) { barline(&mut systems[index], sheet, staves.len(), right, (x - (space / 2)) as i32); }
+			right = barlines().find(|barline| matches!(barline.location, music_xml::RightLeftMiddle::Right));
+			x += measure.x + (space / 2);
+		}
+		barline(&mut systems[index], sheet, staves.len(), right, x as i32 - (sheet.staff_height/8) as i32); // Final
+		for open in spanners.drain(..) { // Unterminated
+			let mut measure = Measure::new(sheet);
+			measure.spanner(&open, &spanner::Point{x: x as i32, ..open.start}, false);
+			extend(&mut systems[index], measure, xy{x: 0, y: 0});
 		}
 	}
-	graphic
+	systems
+}
+
+// Systems stacked in a window (at 240 pixels per staff height). Graphic with the colour ranges of colored elements
+pub fn layout(font: Font, music: &MusicXML, size: size, options: Options) -> (Graphic, Vec<Colored>) {
+	let sheet = Sheet::new(font).with_defaults(&music.score_partwise.defaults).with_options(options);
+	let scale = Ratio{num: 240, div: sheet.staff_height};
+	let width = scale.rcp().ceil(size.x);
+	let right = scale.rcp()*(scale*((scale.rcp()*size.x - scale.rcp().ceil(scale.ceil(num::div_ceil(sheet.engraving_defaults.thin_barline_thickness,2)))))) as i32;
+	let mut graphic = Graphic::new(scale);
+	let mut colors = Vec::new();
+	for (index, System{graphic: system, colors: system_colors, ..}) in systems(&sheet, music, width, right).into_iter().enumerate() {
+		append(&mut graphic, &mut colors, system, system_colors, xy{x: 0, y: (index as u32 * 2*sheet.staff_distance) as i32});
+	}
+	(graphic, colors)
+}
+
+// Page and bottom line of each system (new page requested, distance from the previous system, distance from the top when first on its page),
+// breaking where requested or where the system would pass the bottom. Top and bottom of each page by index
+fn paginate(systems: impl IntoIterator<Item=(bool, i32, i32)>, height: i32, top: impl Fn(usize) -> i32, bottom: impl Fn(usize) -> i32) -> Vec<(usize, i32)> {
+	let mut placed = Vec::<(usize, i32)>::new();
+	for (new_page, distance, top_distance) in systems {
+		placed.push(match placed.last() {
+			Some(&(page, y)) if !new_page && y + distance + height <= bottom(page) => (page, y + distance + height),
+			last => { let page = last.map_or(0, |&(page, _)| page+1); (page, top(page) + top_distance + height) }
+		});
+	}
+	placed
+}
+
+// Page of size in font units, drawn at the graphic scale, with the colour ranges of colored elements
+pub struct Page { pub size: size, pub graphic: Graphic, pub colors: Vec<Colored> }
+
+// Systems on pages sized from the page layout, between the page (odd or even) and system margins, separated by the system distances. Breaks where a page is full or a new page is requested.
+// Credits are drawn on their page, or else title and creators head the first page
+pub fn pages(font: Font, music: &MusicXML, scale: Ratio, options: Options) -> Vec<Page> {
+	let ScorePartwise{defaults, work, identification, credits, ..} = &music.score_partwise;
+	let sheet = Sheet::new(font).with_defaults(defaults).with_options(options);
+	let millimeters = |millimeters: f32| sheet.tenths(millimeters / sheet.millimeters_per_space * 10.); // A4 with 15mm margins by default
+	let size = defaults.page_layout.as_ref().map_or(xy{x: millimeters(210.), y: millimeters(297.)}, |&PageLayout{page_width, page_height, ..}| xy{x: sheet.tenths(page_width), y: sheet.tenths(page_height)});
+	let margins = |odd: bool| defaults.page_layout.as_ref().and_then(|PageLayout{page_margins, ..}| page_margins.iter().find(|PageMargins{r#type, ..}| r#type == "both" || r#type == if odd { "odd" } else { "even" }))
+		.map_or([millimeters(15.); 4], |&PageMargins{left_margin, right_margin, top_margin, bottom_margin, ..}| [left_margin, right_margin, top_margin, bottom_margin].map(|margin| sheet.tenths(margin)));
+	let system_layout = defaults.system_layout.as_ref();
+	let system_margins = system_layout.map_or([0, 0], |&SystemLayout{system_margins: SystemMargins{left_margin, right_margin}, ..}| [left_margin, right_margin].map(|margin| sheet.tenths(margin)));
+	let width = [true, false].map(|odd| { let [left, right, ..] = margins(odd); size.x - left - right }).into_iter().min().unwrap() - system_margins[0] - system_margins[1]; // Fits both odd and even pages
+	let [left, right, ..] = margins(true);
+	let height = sheet.y(0, 0) - sheet.y(1, 8); // Top line to bottom line
+	let mut headers = Measure::new(&sheet);
+	let [_, _, top, _] = margins(true);
+	let header = if credits.is_empty() { headers.headers(defaults.word_font.as_ref().map_or(ui::text::default_font()[0], |font| sheet.text_face(font)), work.as_ref(), identification, left, size.x - right, top) } else { top }; // Bottom
+	let systems = systems(&sheet, music, width as u32, width - (sheet.engraving_defaults.thin_barline_thickness/2) as i32);
+	let placed = paginate(systems.iter().map(|&System{new_page, layout, ..}| {
+		let distance = |distance: fn(&SystemLayout) -> Option<f32>, default: i32| layout.and_then(distance).or_else(|| system_layout.and_then(distance)).map_or(default, |tenths| sheet.tenths(tenths));
+		let system_distance = distance(|layout| layout.system_distance, (sheet.staff_distance - sheet.staff_height) as i32); // From the bottom line of the previous system to the top line
+		(new_page, system_distance, distance(|layout| layout.top_system_distance, system_distance))
+	}), height, |page| { let [_, _, top, _] = margins(page % 2 == 0); if page == 0 { std::cmp::max(top, header) } else { top } }, |page| size.y - margins(page % 2 == 0)[3]);
+	let mut pages = (0..placed.last().map_or(0, |&(page, _)| page+1)).map(|_| Page{size: size.map(|&v| v as u32), graphic: Graphic::new(scale), colors: Vec::new()}).collect::<Vec<_>>();
+	for (System{graphic, colors, ..}, (page, y)) in systems.into_iter().zip(placed) {
+		let [left, ..] = margins(page % 2 == 0);
+		let page = &mut pages[page];
+		append(&mut page.graphic, &mut page.colors, graphic, colors, xy{x: left + system_margins[0], y});
+	}
+	let mut headers = Some(headers);
+	for (index, page) in pages.iter_mut().enumerate() {
+		let mut measure = headers.take().unwrap_or_else(|| Measure::new(&sheet));
+		for Credit{credit_words, ..} in credits.iter().filter(|credit| credit.page as usize == index+1) { for words in credit_words { measure.credit(size.y, words); } }
+		let Measure{graphic, colors, ..} = measure;
+		append(&mut page.graphic, &mut page.colors, graphic, colors, xy{x: 0, y: 0});
+	}
+	pages
+}
+
+// Pages one under the other, scaled to the window width
+pub fn paged(font: Font, music: &MusicXML, size: size, options: Options) -> (Graphic, Vec<Colored>) {
+	let pages = pages(font, music, Ratio{num: 1, div: 1}, options);
+	let mut graphic = Graphic::new(Ratio{num: size.x, div: pages.first().map_or(1, |page| page.size.x)});
+	let mut colors = Vec::new();
+	for (index, Page{size, graphic: page, colors: page_colors}) in pages.into_iter().enumerate() { append(&mut graphic, &mut colors, page, page_colors, xy{x: 0, y: (index as u32 * size.y) as i32}); }
+	(graphic, colors)
+}
+
+#[cfg(test)] mod tests {
+	#[test] fn paginate() { // Breaks before the third system as requested, and before the fifth past the bottom
+		let placed = super::paginate([(false, 10, 20), (false, 10, 20), (true, 10, 20), (false, 10, 20), (false, 10, 20)], 40, |_| 100, |_| 250);
+		assert_eq!(placed, [(0, 160), (0, 210), (1, 160), (1, 210), (2, 160)]);
+		assert_eq!(placed.last().unwrap().0 + 1, 3);
+	}
+	fn barline(xml: &str) -> crate::music_xml::Barline { crate::xml::from_node(crate::xml::parse(xml.as_bytes()).unwrap().root_element()).unwrap() }
+	#[test] fn right_barline() { // Backward repeat followed by a forward repeat
+		let backward = barline(r#"<barline location="right"><bar-style>light-heavy</bar-style><repeat direction="backward"/></barline>"#);
+		assert!(super::right_barline(Some(&backward), true));
+		assert!(!super::right_barline(None, true));
+		assert!(!super::right_barline(Some(&barline(r#"<barline location="right"><bar-style>regular</bar-style></barline>"#)), true));
+		assert!(super::right_barline(None, false));
+	}
 }
@@ -10,11 +10,39 @@ mod sheet;
 mod staff;
 mod measure;
 mod beam;
+mod rest;
+mod tuplet;
+mod articulation;
+mod spanner;
+mod ornament;
+mod fingering;
+mod notation;
+mod barline;
 mod attributes;
 mod direction;
-mod layout; use layout::layout;
+mod credit;
+mod label;
+mod colored;
+mod layout; use layout::{layout, paged};
 fn main() -> ui::Result { 
     let font = &*Box::leak::<'static>(Default::default());
     let sheet = &*Box::leak::<'static>(xml::from_document(&xml::parse(&std::fs::read("../Documents/Scores/sheet.xml")?)?)?);
-    ui::run(ui::graphic::Widget(|size| Ok(layout(font, sheet, size)))) 
+    let args = std::env::args().collect::<Vec<_>>();
+    let options = sheet::Options{
+        positions: args.iter().any(|arg| arg == "--positions"), // Keeps the engraver placement
+        measure_numbering: match args.iter().find_map(|arg| arg.strip_prefix("--measure-numbers=")) {
+            Some("none") => sheet::MeasureNumbering::None,
+            Some("measure") => sheet::MeasureNumbering::Every(1),
+            Some(every) => every.parse().ok().filter(|&every| every > 0).map_or(sheet::MeasureNumbering::System, sheet::MeasureNumbering::Every),
+            None => sheet::MeasureNumbering::System
+        },
+        boxed_measure_numbers: args.iter().any(|arg| arg == "--boxed-measure-numbers"),
+    };
+    if args.iter().any(|arg| arg == "--performance-order") { // Measure numbers of each part in playback order, instead of the engraving
+        use itertools::Itertools;
+        for part in &sheet.score_partwise.parts { println!("{}: {}", part.id, music::unroll(part).into_iter().map(|index| part.measures[index].number).format(" ")); }
+        return Ok(());
+    }
+    let pages = args.iter().any(|arg| arg == "--pages"); // Paginated with credits, instead of systems across the window
+    ui::run(colored::Widget(|size| Ok(if pages { paged(font, sheet, size, options) } else { layout(font, sheet, size, options) }))) 
 }
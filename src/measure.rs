@@ -1,30 +1,115 @@
-use {derive_more::{Deref, DerefMut}, ui::graphic::{Graphic, Glyph}, ::xy::xy, crate::{sheet::Sheet, music_xml::Pitch, staff::StaffRef, music::BeamedMusicData}};
+use {std::{ops::Range, collections::BTreeMap}, derive_more::{Deref, DerefMut}, ui::graphic::{Graphic, Glyph, vertical}, ::xy::{xy, Rect}, vector::MinMax, crate::{sheet::{Sheet, horizontal}, music_xml::{Color, Justify, VAlign}, music::BeamedMusicData}};
 
-#[derive(Deref)] pub struct Measure<'t> { #[deref] pub sheet: &'t Sheet, pub graphic: Graphic }
+// Colour of ranges of graphic rects, parallelograms and glyphs (ui graphics are monochrome)
+#[derive(Debug, Clone)] pub struct Colored { pub color: Color, pub rects: Range<usize>, pub parallelograms: Range<usize>, pub glyphs: Range<usize> }
+
+#[derive(Deref)] pub struct Measure<'t> { #[deref] pub sheet: &'t Sheet, pub graphic: Graphic, pub scale: f32, color: Option<(Color, [usize; 3])>, pub colors: Vec<Colored> }
 impl<'t> Measure<'t> {
-	fn new(sheet: &'t Sheet) -> Self { Self{sheet, graphic: Graphic::new(Default::default())} }
-	fn last_advance(&self) -> i32 { self.graphic.glyphs.last().map(|g:&Glyph| g.top_left.x + g.face.glyph_hor_advance(g.id).unwrap() as i32).unwrap_or(0) }
+	pub fn new(sheet: &'t Sheet) -> Self { Self{sheet, graphic: Graphic::new(Default::default()), scale: 1., color: None, colors: Vec::new()} }
+	// Colours the following pushes (until the next change)
+	pub fn set_color(&mut self, color: Option<Color>) {
+		let lengths = [self.graphic.rects.len(), self.graphic.parallelograms.len(), self.graphic.glyphs.len()];
+		if let Some((color, [rects, parallelograms, glyphs])) = self.color.take() {
+			if lengths != [rects, parallelograms, glyphs] { self.colors.push(Colored{color, rects: rects..lengths[0], parallelograms: parallelograms..lengths[1], glyphs: glyphs..lengths[2]}); }
+		}
+		self.color = color.map(|color| (color, lengths));
+	}
+	fn last_advance(&self) -> i32 { self.graphic.glyphs.last().map(|g:&Glyph| g.top_left.x + (g.face.glyph_hor_advance(g.id).unwrap() as f32 * g.scale) as i32).unwrap_or(0) }
 	pub fn push_glyph_id(&mut self, x: u32, staff_index: usize, step: i8, dy: i32, id: ttf_parser::GlyphId) {
 		self.graphic.glyphs.push(Glyph{top_left: xy{
-			x: x as i32 + self.sheet.face.glyph_hor_side_bearing(id).unwrap() as i32,
-			y: self.sheet.y(staff_index, step) - self.sheet.face.glyph_bounding_box(id).unwrap().y_max as i32 + dy,
-		}, face: self.sheet.face, id, scale: 1.})
+			x: x as i32 + (self.sheet.face.glyph_hor_side_bearing(id).unwrap() as f32 * self.scale) as i32,
+			y: self.sheet.y(staff_index, step) - (self.sheet.face.glyph_bounding_box(id).unwrap().y_max as f32 * self.scale) as i32 + dy,
+		}, face: self.sheet.face, id, scale: self.scale})
 	}
 	pub fn push_glyph(&mut self, x: u32, staff_index: usize, step: i8, dy: i32, id: char) {
 		self.push_glyph_id(x, staff_index, step, dy, self.sheet.face.glyph_index(id).unwrap())
 	}
-	pub fn push_glyph_at_pitch(&mut self, x: u32, staff: StaffRef, pitch: &Pitch, id: char) {
-		self.push_glyph(x, staff.index, staff.step(pitch), 0, id)
+	// Centers a row of glyphs on x beyond the edge step, above or below, and moves the edge past it. Returns the row right and center step
+	pub fn push_stacked(&mut self, x: i32, staff_index: usize, edge: &mut i8, above: bool, glyphs: &[char], scale: f32) -> (i32, i8) {
+		let half = (self.sheet.staff_height/8) as i32;
+		let bbox = |glyph| self.sheet.bbox(glyph);
+		let (y_min, y_max) = (glyphs.iter().map(|&glyph| bbox(glyph).y_min as i32).min().unwrap(), glyphs.iter().map(|&glyph| bbox(glyph).y_max as i32).max().unwrap());
+		let height = ((((y_max - y_min) as f32 * scale) as i32 + half - 1) / half) as i8;
+		let center = if above { *edge + 1 + height/2 } else { *edge - 1 - height/2 };
+		*edge = if above { center + (height+1)/2 } else { center - (height+1)/2 };
+		let width = glyphs.iter().map(|&glyph| (self.sheet.advance(glyph) as f32 * scale) as i32).sum::<i32>();
+		let previous_scale = self.scale;
+		self.scale = previous_scale * scale;
+		let mut glyph_x = x - width/2;
+		for &glyph in glyphs {
+			self.push_glyph(glyph_x as u32, staff_index, center, ((y_min + y_max) as f32 * scale) as i32 / 2, glyph);
+			glyph_x += (self.sheet.advance(glyph) as f32 * scale) as i32;
+		}
+		self.scale = previous_scale;
+		(glyph_x, center)
+	}
+	// Glyph offsets of a text line with an em of size
+	pub fn text_layout(face: &'static ui::font::File<'static>, size: u32, text: &str) -> Vec<(i32, ttf_parser::GlyphId)> {
+		use ui::text::{Plain, View, layout, Glyph, unicode_segmentation::UnicodeSegmentation};
+		let scale = size as f32 / face.units_per_em() as f32;
+		let text = View::new_with_face(face, Plain(text));
+		layout(&text.font, text.data.0.graphemes(true).enumerate()).map(|Glyph{x, id, ..}| ((x as f32 * scale) as i32, id)).collect()
+	}
+	pub fn text_width(face: &'static ui::font::File<'static>, size: u32, text: &str) -> i32 {
+		let scale = size as f32 / face.units_per_em() as f32;
+		Self::text_layout(face, size, text).last().map_or(0, |&(x, id)| x + (face.glyph_hor_advance(id).unwrap() as f32 * scale) as i32)
+	}
+	// Text with its baseline origin at (x, y) and an em of size. Returns the right
+	pub fn push_text_with_face(&mut self, face: &'static ui::font::File<'static>, x: i32, y: i32, size: u32, text: &str) -> i32 {
+		let scale = size as f32 / face.units_per_em() as f32;
+		for (dx, id) in Self::text_layout(face, size, text) {
+			self.graphic.glyphs.push(ui::graphic::Glyph{top_left: xy{
+				x: x + dx + (face.glyph_hor_side_bearing(id).unwrap() as f32 * scale) as i32,
+				y: y - (face.glyph_bounding_box(id).map_or(0, |bbox| bbox.y_max) as f32 * scale) as i32,
+			}, face, id, scale});
+		}
+		x + Self::text_width(face, size, text)
+	}
+	pub fn push_text(&mut self, x: i32, y: i32, size: u32, text: &str) -> i32 { self.push_text_with_face(ui::text::default_font()[0], x, y, size, text) }
+	// Text justified on x and aligned vertically on y (default left and baseline). Returns its bounds
+	pub fn push_aligned_text(&mut self, face: &'static ui::font::File<'static>, size: u32, xy{x, y}: xy<i32>, justify: Option<Justify>, valign: Option<VAlign>, text: &str) -> Rect {
+		let scale = size as f32 / face.units_per_em() as f32;
+		let (ascender, descender) = ((face.ascender() as f32 * scale) as i32, (face.descender() as f32 * scale) as i32);
+		let y = y + match valign { Some(VAlign::Top) => ascender, Some(VAlign::Middle) => (ascender + descender) / 2, Some(VAlign::Bottom) => descender, Some(VAlign::Baseline)|None => 0 };
+		let width = Self::text_width(face, size, text);
+		let left = match justify { Some(Justify::Center) => x - width/2, Some(Justify::Right) => x - width, Some(Justify::Left)|None => x };
+		let right = self.push_text_with_face(face, left, y, size, text);
+		Rect{min: xy{x: left, y: y - ascender}, max: xy{x: right, y: y - descender}}
+	}
+	// Frames bounds (of a text) with a margin. Returns the frame bounds
+	pub fn push_box(&mut self, Rect{min, max}: Rect) -> Rect {
+		let (margin, thickness) = ((self.sheet.staff_height/16) as i32, self.sheet.engraving_defaults.thin_barline_thickness);
+		let (min, max) = (xy{x: min.x - margin, y: min.y - margin}, xy{x: max.x + margin, y: max.y + margin});
+		self.graphic.rects.extend([horizontal(min.y, thickness, min.x, max.x), horizontal(max.y, thickness, min.x, max.x), vertical(min.x, thickness, min.y, max.y), vertical(max.x, thickness, min.y, max.y)]);
+		Rect{min, max}
+	}
+	// Ledger lines for steps outside the staff, spanning heads from x0 to x1
+	pub fn ledgers(&mut self, staff_index: usize, MinMax{min, max}: MinMax<i8>, x0: u32, x1: u32) {
+		let &crate::font::SMuFL::EngravingDefaults{leger_line_thickness, leger_line_extension, ..} = &self.sheet.engraving_defaults;
+		for step in (10..=max).step_by(2).chain((min..=-2).filter(|step| step % 2 == 0)) {
+			self.graphic.rects.push(horizontal(self.sheet.y(staff_index, step), leger_line_thickness, x0 as i32 - leger_line_extension as i32, x1 as i32 + leger_line_extension as i32));
+		}
 	}
 }
 
-#[derive(Deref, DerefMut)] pub struct MeasureLayoutContext<'t> { #[deref]#[deref_mut] pub measure: Measure<'t>, t: u32, pub x: u32}
+// onsets: head x of each onset shared by the voices (seeded by a previous layout pass). ends: right of the heads and rests of each onset
+#[derive(Deref, DerefMut)] pub struct MeasureLayoutContext<'t> { #[deref]#[deref_mut] pub measure: Measure<'t>, pub t: u32, pub x: u32, pub system_start: bool, pub beams: usize, pub tuplets: Vec<crate::tuplet::Span>, pub spanners: Vec<crate::spanner::Event>, pub onsets: BTreeMap<u32, u32>, ends: BTreeMap<u32, u32>}
 impl<'t> MeasureLayoutContext<'t> {
-	pub fn new(sheet: &'t Sheet) -> Self { Self{measure: Measure::new(sheet), t: 0, x: 0} }
+	pub fn new(sheet: &'t Sheet) -> Self { Self{measure: Measure::new(sheet), t: 0, x: 0, system_start: false, beams: 0, tuplets: Vec::new(), spanners: Vec::new(), onsets: BTreeMap::new(), ends: BTreeMap::new()} }
 }
 impl MeasureLayoutContext<'_> {
 	pub fn space(&self) -> u32 { self.measure.sheet.staff_height / 4 }
 	pub fn advance(&mut self, space: u32) { self.x = self.measure.last_advance() as u32 + space; }
+	// Past all the elements (the last glyph may belong to an earlier onset of another voice)
+	pub fn end(&mut self, space: u32) { self.x = std::cmp::max(self.measure.last_advance() as u32, self.ends.values().copied().max().unwrap_or(0)) + space; }
+	// Aligns a head (or rest) at onset t with the other voices, at least at x. Returns its x
+	pub fn onset(&mut self, t: u32, x: u32, width: u32) -> u32 {
+		let x = std::cmp::max(x, self.onsets.get(&t).copied().unwrap_or(0));
+		self.onsets.insert(t, x);
+		let end = self.ends.entry(t).or_default();
+		*end = std::cmp::max(*end, x + width);
+		x
+	}
 }
 
 #[derive(Deref, DerefMut)] pub struct MusicLayoutContext<'t, I> { pub music_data: I, #[deref]#[deref_mut] pub layout_context: MeasureLayoutContext<'t> }
@@ -32,7 +117,10 @@ impl<'t, I:Iterator<Item=(u32, BeamedMusicData<'t>)>> Iterator for MusicLayoutCo
 	type Item = (u32, u32, BeamedMusicData<'t>);
 	fn next(&mut self) -> Option<Self::Item> {
 		self.music_data.next().map(|(t, e)| { // Advances horizonal position as measure is constructed
-			if t > self.t { let space = self.space(); self.advance(space); }
+			if t > self.t { // Past the elements of the previous onsets of all voices (not the later onsets of a beam of another voice)
+				let space = self.space();
+				self.x = std::cmp::max(self.x, self.ends.range(..t).map(|(_, &end)| end).max().unwrap_or(0)) + space;
+			}
 			self.t = t;
 			(t, self.x, e)
 		})
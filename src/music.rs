@@ -1,22 +1,42 @@
 // Opiniated features for MusicXML
-use crate::music_xml::{Step, ClefSign, Clef, Pitch, Note, NoteData, NoteTypeValue, Backup, Forward, MusicData};
+use crate::music_xml::{Step, ClefSign, Clef, Pitch, Note, NoteData, NoteTypeValue, StemDirection, Beam, BeamValue, Notations, Notation, ArticulationData, Placement, Backup, Forward, MusicData, Part, Barline, Repeat, BackwardForward, Ending, StartStopDiscontinue, RightLeftMiddle, Direction, Sound, PartList, ScorePart, PartGroupOrScorePart};
 
 impl From<&Step> for i8 { fn from(step: &Step) -> Self { use Step::*; match step { C=>0, D=>1, E=>2, F=>3, G=>4, A=>5, B=>6 } } }
 
-impl Pitch {
-	pub fn new(clef: &Clef, step: &Step) -> Self {
-		use Step::*;
-		match clef {
-			Clef{sign: ClefSign::G,..} => Pitch{step: *step, octave: Some(match step { G|A|B => 4, C|D|E|F => 5 }), alter: None},
-			Clef{sign: ClefSign::F,..} => Pitch{step: *step, octave: Some(match step { A|B => 2, C|D|E|F|G => 3 }), alter: None},
-		}
-	}
+impl Clef {
+	pub fn step(&self) -> i8 { use ClefSign::*; 2*(self.line.unwrap_or(match self.sign { G=>2, F=>4, C=>3 }) as i8 - 1) } // Of the line of the sign
+	#[allow(non_snake_case)] pub fn C4(&self) -> i8 { use ClefSign::*; self.step() - match self.sign { G=>4, F=>-4, C=>0 } }
 }
 impl From<&Pitch> for i8 { fn from(pitch: &Pitch) -> Self { (pitch.octave.unwrap_or(4) as i8 - 4)*7 + i8::from(&pitch.step) } }
 
 impl Note {
     pub fn pitch(&self) -> Option<&Pitch> { if let NoteData::Pitch(pitch) = &self.content { Some(pitch) } else { None } }
-    pub fn has_stem(&self) -> bool { self.r#type.as_ref().unwrap().value <= NoteTypeValue::Half }
+    pub fn notations(&self) -> impl Iterator<Item=&Notation> { self.notations.iter().flat_map(|Notations{content}| content) }
+    pub fn beam_continues(&self) -> bool { self.beams.iter().any(|Beam{value, number}| number.unwrap_or(1) == 1 && matches!(value, BeamValue::Begin|BeamValue::Continue)) }
+    pub fn voice_stem_direction(&self) -> StemDirection { if self.voice.unwrap_or(1) % 2 == 1 { StemDirection::Up } else { StemDirection::Down } }
+    pub fn has_stem(&self) -> bool {
+		if let Some(StemDirection::None) = self.stem.as_ref().map(|stem| stem.value) { false } // Explicit <stem>none</stem>
+		else { self.r#type.as_ref().map_or(self.stem.is_some(), |r#type| r#type.value <= NoteTypeValue::Half) } // Whole, breve: unstemmed
+	}
+}
+
+impl PartList {
+	pub fn score_parts(&self) -> impl Iterator<Item=&ScorePart> { std::iter::once(&self.score_part).chain(self.part_group_score_part.iter().filter_map(|item| if let PartGroupOrScorePart::ScorePart(score_part) = item { Some(score_part) } else { None })) }
+}
+
+impl ArticulationData {
+	pub fn placement(&self) -> Option<Placement> {
+		use ArticulationData::*;
+		match self {
+			Accent(a)|StrongAccent(a)|Staccato(a)|Tenuto(a)|DetachedLegato(a)|Staccatissimo(a)|Spiccato(a)|Scoop(a)|Plop(a)|Doit(a)|Falloff(a)|Stress(a)|Unstress(a)|SoftAccent(a) => a.placement,
+			BreathMark(a)|Caesura(a) => a.placement,
+		}
+	}
+	// Stacking order from the notehead outwards
+	pub fn order(&self) -> u8 {
+		use ArticulationData::*;
+		match self { Staccato(_)|Staccatissimo(_)|Spiccato(_) => 0, DetachedLegato(_) => 1, Tenuto(_) => 2, Accent(_)|SoftAccent(_)|Stress(_)|Unstress(_) => 3, StrongAccent(_) => 4, _ => 5 }
+	}
 }
 
 impl std::fmt::Display for MusicData { fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
@@ -37,32 +57,137 @@ pub fn sort_by_start_time<'t, I: IntoIterator<Item=&'t MusicData>>(it: I) -> imp
 			MusicData::Note(Note{duration: Some(duration), ..}) => { *next_t = std::cmp::max(*next_t, t + duration); /*duration from first (longest)*/},
 			MusicData::Backup(Backup{duration}) => { assert2::assert!(t >= *duration); *next_t = t - duration; },
 			MusicData::Forward(Forward{duration}) => { *next_t = t + duration; },
-			MusicData::Note(Note{duration: None, ..})|MusicData::Print(_)|MusicData::Attributes(_)|MusicData::Direction(_)|MusicData::Barline(_) => {}
+			MusicData::Note(Note{duration: None, ..})|MusicData::Print(_)|MusicData::Attributes(_)|MusicData::Direction(_)|MusicData::Barline(_)|MusicData::Sound(_) => {}
 		}
 		Some((t, music_data))
 	} f}).sorted_by_key(|&(t,_)| t)
 }
 
 #[derive(Debug)] pub enum BeamedMusicData<'t> { Beam(Vec::<Vec<&'t Note>>), MusicData(&'t MusicData) }
+// Groups chords of each (staff, voice) into beams. Unbeamed chords are single element groups.
 pub fn batch_beamed_group_of_notes<'t, I: IntoIterator<Item=(u32,&'t MusicData)>>(it: I) -> impl Iterator<Item=(u32,BeamedMusicData<'t>)> {
 	use itertools::Itertools;
-    it.into_iter().peekable().batching({
-	    let mut beam = None;
-	    let mut chord = None;
-	    move |it| loop {
-			if let Some((_, MusicData::Note(Note{stem: Some(_),..}))) = it.peek() {
-				let Some((t, MusicData::Note(note))) = it.next() else { unreachable!() };
-				if let Note{chord: None, ..} = note { // Next chord
-					if let Some((t,chord)) = chord.take() { // Commit any pending chord
-						let (_, beam) = beam.get_or_insert((t, Vec::new()));
-						beam.push(chord);
-					}
+	let mut beams = Vec::<((Option<u8>, Option<u8>), u32, Vec<Vec<&Note>>)>::new(); // Pending beam per voice
+	let mut output = Vec::new();
+	for (t, music_data) in it {
+		if let MusicData::Note(note@Note{content: NoteData::Pitch(_), ..}) = music_data {
+			let voice = (note.staff.map(|staff| staff.0), note.voice);
+			if let Some(index) = beams.iter().position(|(key,_,_)| key == &voice) {
+				let (_, _, beam) = &mut beams[index];
+				if note.chord.is_some() { beam.last_mut().unwrap().push(note); continue; }
+				let last = beam.last().unwrap().first().unwrap();
+				if !last.beam_continues() || last.grace.is_some() != note.grace.is_some() { // Commit ended beam (grace notes are beamed separately from their principal note)
+					let (_, t, beam) = beams.remove(index);
+					output.push((t, BeamedMusicData::Beam(beam)));
+				} else { beam.push(vec![note]); continue; }
+			}
+			beams.push((voice, t, vec![vec![note]]));
+		}
+		else { output.push((t, BeamedMusicData::MusicData(music_data))); }
+	}
+	output.extend(beams.into_iter().map(|(_, t, beam)| (t, BeamedMusicData::Beam(beam))));
+	output.into_iter().sorted_by_key(|&(t,_)| t)
+}
+
+// Performed order of measure indices: nested repeats (ignored after a jump), endings by pass number, then da capo / dal segno, to coda and fine
+pub fn unroll(Part{measures, ..}: &Part) -> Vec<usize> {
+	let barlines = |index: usize| measures[index].music_data.iter().filter_map(|music_data| if let MusicData::Barline(barline) = music_data { Some(barline) } else { None });
+	let sounds = |index: usize| measures[index].music_data.iter().filter_map(|music_data| match music_data { MusicData::Sound(sound)|MusicData::Direction(Direction{sound: Some(sound), ..}) => Some(sound), _ => None });
+	let forward = |index| barlines(index).any(|barline| matches!(barline, Barline{repeat: Some(Repeat{direction: BackwardForward::Forward, ..}), ..}));
+	let backward = |index| barlines(index).find_map(|barline| if let Barline{repeat: Some(Repeat{direction: BackwardForward::Backward, times}), ..} = barline { Some(*times) } else { None });
+	let find = |key: &dyn Fn(&Sound) -> Option<&String>, name: &String| (0..measures.len()).find(|&index| sounds(index).any(|sound| key(sound) == Some(name)));
+	let endings = { // Ending numbers of each measure between an ending start and its stop or discontinue
+		let mut current = None;
+		(0..measures.len()).map(|index| {
+			for barline in barlines(index) {
+				if let Barline{location: RightLeftMiddle::Left, ending: Some(Ending{number, r#type: StartStopDiscontinue::Start, ..}), ..} = barline {
+					current = Some(number.split(',').filter_map(|number| number.trim().parse::<u8>().ok()).collect::<Vec<_>>());
 				}
-				let (_, chord) = chord.get_or_insert((t, Vec::new()));
-				chord.push(note);
 			}
-			else if let Some((t, beam)) = beam.take() { return Some((t, BeamedMusicData::Beam(beam))); }
-		    else { let (t, music_data) = it.next()?; return Some((t, BeamedMusicData::MusicData(music_data))); }
-	    }
-    })
+			let numbers = current.clone();
+			if barlines(index).any(|barline| matches!(barline.ending, Some(Ending{r#type: StartStopDiscontinue::Stop|StartStopDiscontinue::Discontinue, ..}))) { current = None; }
+			numbers
+		}).collect::<Vec<_>>()
+	};
+	let (last, passes) = { // Endings played after a jump, and default repeat times within endings (up to the last ending number)
+		let (mut last, mut passes) = (vec![false; measures.len()], vec![2; measures.len()]);
+		let mut following = None; // Last ending of the run, and largest number from the measure on
+		for index in (0..measures.len()).rev() {
+			if let Some(numbers) = &endings[index] {
+				let (last_numbers, max) = following.get_or_insert((numbers, 2));
+				*max = std::cmp::max(*max, numbers.iter().copied().max().unwrap_or(0));
+				last[index] = *last_numbers == numbers;
+				passes[index] = *max;
+			} else { following = None; }
+		}
+		(last, passes)
+	};
+	let mut order = Vec::new();
+	let mut sections = vec![(0, 1, false)]; // Start, pass and whether opened by a forward barline, of each nested repeated section
+	let (mut index, mut repeated) = (0, std::collections::HashMap::<usize, u8>::new());
+	let (mut jumped, mut jumps) = (false, std::collections::HashSet::new()); // After a da capo or dal segno
+	let mut back = false; // Returning to the repeat start
+	while index < measures.len() {
+		if !back && index > 0 && endings[index].is_none() && endings[index-1].is_some() { // Leaves the endings of a repeated section
+			if matches!(sections.last(), Some(&(_, pass, _)) if pass > 1) { sections.pop(); }
+			if let None|Some(&(_, 1, false)) = sections.last() { sections.pop(); sections.push((index, 1, false)); }
+		}
+		if !back && forward(index) { // Enters a new repeated section, nested unless only implicitly started
+			if let Some(&(_, 1, false)) = sections.last() { sections.pop(); }
+			sections.push((index, 1, true));
+		}
+		back = false;
+		let pass = sections.last().map_or(1, |&(_, pass, _)| pass);
+		if let Some(numbers) = &endings[index] { if !(if jumped { last[index] } else { numbers.contains(&pass) }) { index += 1; continue; } } // Ending of another pass
+		order.push(index);
+		if jumped && sounds(index).any(|sound| sound.fine.is_some()) { break; }
+		if jumped { if let Some(coda) = sounds(index).find_map(|sound| sound.tocoda.as_ref()).and_then(|name| find(&|sound| sound.coda.as_ref(), name)).filter(|&coda| coda > index) { index = coda; continue; } }
+		if !jumped { if let Some(times) = backward(index) {
+			let times = times.unwrap_or(passes[index]);
+			let count = repeated.entry(index).or_insert(1);
+			let section = sections.last_mut().unwrap();
+			if *count < times { *count += 1; section.1 += 1; index = section.0; back = true; continue; }
+			repeated.remove(&index);
+			sections.pop(); // Exhausted
+			if sections.is_empty() { sections.push((index + 1, 1, false)); }
+		}}
+		if jumps.insert(index) {
+			if sounds(index).any(|sound| sound.dacapo == Some(true)) { jumped = true; index = 0; continue; }
+			if let Some(segno) = sounds(index).find_map(|sound| sound.dalsegno.as_ref()).and_then(|name| find(&|sound| sound.segno.as_ref(), name)) { jumped = true; index = segno; continue; } // Ignored without its segno
+		}
+		index += 1;
+	}
+	order
+}
+
+#[cfg(test)] mod tests {
+	fn unroll(measures: &[&str]) -> Vec<usize> {
+		let measures = measures.iter().enumerate().map(|(index, data)| format!("<measure number=\"{}\" width=\"1\">{data}</measure>", index+1)).collect::<String>();
+		let xml = format!("<part id=\"P1\">{measures}</part>");
+		let document = crate::xml::parse(xml.as_bytes()).unwrap();
+		super::unroll(&crate::xml::from_node(document.root_element()).unwrap())
+	}
+	const FORWARD: &str = r#"<barline location="left"><repeat direction="forward"/></barline>"#;
+	const BACKWARD: &str = r#"<barline location="right"><repeat direction="backward"/></barline>"#;
+	fn ending(number: &str, stop: &str) -> [String; 2] { [format!(r#"<barline location="left"><ending number="{number}" type="start"/></barline>"#), format!(r#"<barline location="right"><ending number="{number}" type="{stop}"/>{}</barline>"#, if stop == "stop" { r#"<repeat direction="backward"/>"# } else { "" })] }
+
+	#[test] fn repeat() {
+		assert_eq!(unroll(&["", BACKWARD, ""]), [0, 1, 0, 1, 2]);
+		assert_eq!(unroll(&["", FORWARD, BACKWARD, ""]), [0, 1, 2, 1, 2, 3]);
+	}
+	#[test] fn times() { assert_eq!(unroll(&[&format!(r#"{FORWARD}<barline location="right"><repeat direction="backward" times="3"/></barline>"#), ""]), [0, 0, 0, 1]); }
+	#[test] fn endings() {
+		let ([first, repeat], [second, end]) = (ending("1", "stop"), ending("2", "discontinue"));
+		assert_eq!(unroll(&["", &(first+&repeat), &(second+&end), ""]), [0, 1, 0, 2, 3]);
+	}
+	#[test] fn shared_ending() {
+		let ([first, repeat], [third, end]) = (ending("1, 2", "stop"), ending("3", "discontinue"));
+		assert_eq!(unroll(&["", &(first+&repeat), &(third+&end), ""]), [0, 1, 0, 1, 0, 2, 3]);
+	}
+	#[test] fn nested() { assert_eq!(unroll(&[FORWARD, &(FORWARD.to_owned()+BACKWARD), BACKWARD, ""]), [0, 1, 1, 2, 0, 1, 1, 2, 3]); }
+	#[test] fn da_capo_al_fine() { assert_eq!(unroll(&["", r#"<sound fine="yes"/>"#, r#"<sound dacapo="yes"/>"#]), [0, 1, 2, 0, 1]); }
+	#[test] fn dal_segno_al_coda() {
+		assert_eq!(unroll(&["", r#"<sound segno="segno"/>"#, r#"<sound tocoda="coda"/>"#, r#"<sound dalsegno="segno"/>"#, r#"<sound coda="coda"/>"#]), [0, 1, 2, 3, 1, 2, 4]);
+		assert_eq!(unroll(&["", r#"<sound dalsegno="missing"/>"#, ""]), [0, 1, 2]);
+	}
 }
@@ -1,7 +1,7 @@
 #![allow(dead_code)]
 use serde_derive::Deserialize;
 
-#[derive(Clone, Copy, Debug, Default)] struct Color { a: u8, r: u8, g: u8, b: u8 }
+#[derive(Clone, Copy, Debug, Default)] pub struct Color { pub a: u8, pub r: u8, pub g: u8, pub b: u8 }
 impl<'de> serde::Deserialize<'de> for Color {
 	fn deserialize<D:serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
 		struct Visitor;
@@ -19,13 +19,13 @@ impl<'de> serde::Deserialize<'de> for Color {
 }
 #[derive(Debug, Deserialize)]#[serde(rename="work",rename_all="kebab-case")]
 pub struct Work {
-	work_title: Option<String>,
+	pub work_title: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="creator",rename_all="kebab-case")]
 pub struct Creator {
-	r#type: String,
-	#[serde(rename="$")] creator: String,
+	pub r#type: String,
+	#[serde(rename="$")] pub creator: String,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="supports",rename_all="kebab-case")]
@@ -66,7 +66,7 @@ pub struct Miscellaneous {
 #[derive(Debug, Deserialize)]#[serde(rename="identification",rename_all="kebab-case")]
 pub struct Identification {
 	#[serde(rename="creator*")]
-	creator: Vec<Creator>,
+	pub creator: Vec<Creator>,
 	#[serde(rename="rights*")]
 	rights: Vec<String>,
 	encoding: Option<Encoding>,
@@ -76,45 +76,45 @@ pub struct Identification {
 
 #[derive(Debug, Deserialize)]#[serde(rename="scaling",rename_all="kebab-case")]
 pub struct Scaling {
-	millimeters: uf32,
-	tenths: u32,
+	pub millimeters: uf32,
+	pub tenths: u32,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="page-margins",rename_all="kebab-case")]
 pub struct PageMargins {
-	r#type: /*odd,even,both*/String,
-	left_margin: uf32,
-	right_margin: uf32,
-	top_margin: uf32,
-	bottom_margin: uf32,
+	pub r#type: /*odd,even,both*/String,
+	pub left_margin: uf32,
+	pub right_margin: uf32,
+	pub top_margin: uf32,
+	pub bottom_margin: uf32,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="page-layout",rename_all="kebab-case")]
 pub struct PageLayout {
-	page_height: uf32,
-	page_width: uf32,
+	pub page_height: uf32,
+	pub page_width: uf32,
 	#[serde(rename="page-margins{0,2}")]
-	page_margins: Vec<PageMargins>,
+	pub page_margins: Vec<PageMargins>,
 }
 
-#[derive(Debug, Deserialize)]#[serde(rename="type",rename_all="lowercase")]
-enum LineWidthType { Beam, Bracket, Dashes, Enclosure, Ending, Extend, #[serde(rename="heavy barline")] HeavyBarline, Leger, #[serde(rename="light barline")] LightBarline, #[serde(rename="octave shift")] OctaveShift, Pedal, #[serde(rename="slur middle")] SlurMiddle, #[serde(rename="slur tip")] SlurTip, Staff, Stem, #[serde(rename="tie middle")] TieMiddle, #[serde(rename="tie tip")] TieTip, #[serde(rename="tuplet bracket")] TupletBracket, Wedge }
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename="type",rename_all="lowercase")]
+pub enum LineWidthType { Beam, Bracket, Dashes, Enclosure, Ending, Extend, #[serde(rename="heavy barline")] HeavyBarline, Leger, #[serde(rename="light barline")] LightBarline, #[serde(rename="octave shift")] OctaveShift, Pedal, #[serde(rename="slur middle")] SlurMiddle, #[serde(rename="slur tip")] SlurTip, Staff, Stem, #[serde(rename="tie middle")] TieMiddle, #[serde(rename="tie tip")] TieTip, #[serde(rename="tuplet bracket")] TupletBracket, Wedge }
 
 #[derive(Debug, Deserialize)]#[serde(rename="line-width",rename_all="kebab-case")]
 pub struct LineWidth {
-	r#type: LineWidthType,
+	pub r#type: LineWidthType,
 	#[serde(rename="$")]
-	tenths: uf32
+	pub tenths: uf32
 }
 
-#[derive(Debug, Deserialize)]#[serde(rename="type",rename_all="kebab-case")]
-enum NoteSizeType { Cue, Grace, GraceCue, Large }
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename="type",rename_all="kebab-case")]
+pub enum NoteSizeType { Cue, Grace, GraceCue, Large }
 
 #[derive(Debug, Deserialize)]#[serde(rename="note-size",rename_all="kebab-case")]
 pub struct NoteSize {
-	r#type: NoteSizeType,
+	pub r#type: NoteSizeType,
 	#[serde(rename="$")]
-	percents: uf32
+	pub percents: uf32
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="type",rename_all="kebab-case")]
@@ -130,9 +130,9 @@ pub struct Distance {
 #[derive(Debug, Deserialize)]#[serde(rename="appearance",rename_all="kebab-case")]
 pub struct Appearance {
 	#[serde(rename="line-width*")]
-	line_width: Vec<LineWidth>,
+	pub line_width: Vec<LineWidth>,
 	#[serde(rename="note-size*")]
-	note_size: Vec<NoteSize>,
+	pub note_size: Vec<NoteSize>,
 	#[serde(rename="distance*")]
 	distance: Vec<Distance>
 }
@@ -150,23 +150,23 @@ pub struct Font {
 	#[serde(rename="font-family@")]
 	pub font_family: Option<String>,
 	#[serde(rename="font-style@")]
-	font_style: Option<String>,
+	pub font_style: Option</*normal,italic*/String>,
 	#[serde(rename="font-size@")]
-	font_size: Option<uf32>,
+	pub font_size: Option<uf32>,
 	#[serde(rename="font-weight@")]
-	font_weight: Option</*normal,bold*/String>
+	pub font_weight: Option</*normal,bold*/String>
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="defaults",rename_all="kebab-case")]
 pub struct Defaults {
-	scaling: Option<Scaling>,
-	page_layout: Option<PageLayout>,
-	system_layout: Option<SystemLayout>,
+	pub scaling: Option<Scaling>,
+	pub page_layout: Option<PageLayout>,
+	pub system_layout: Option<SystemLayout>,
 	#[serde(rename="staff-layout*")]
-	staff_layout: Vec<StaffLayout>,
-	appearance: Option<Appearance>,
+	pub staff_layout: Vec<StaffLayout>,
+	pub appearance: Option<Appearance>,
 	music_font: Option<Font>,
-	word_font: Option<Font>,
+	pub word_font: Option<Font>,
 	#[serde(rename="lyric-font*")]
 	lyric_font: Vec<Font>,
 	lyric_language: Vec<LyricLanguage>
@@ -174,15 +174,15 @@ pub struct Defaults {
 
 #[derive(Debug, Deserialize)]#[serde(rename="position",rename_all="kebab-case")]
 pub struct Position {
-	default_x: Option<f32>,
-	default_y: Option<f32>,
-	relative_x: Option<f32>,
-	relative_y: Option<f32>,
+	pub default_x: Option<f32>,
+	pub default_y: Option<f32>,
+	pub relative_x: Option<f32>,
+	pub relative_y: Option<f32>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="print-style",rename_all="kebab-case")]
 pub struct PrintStyle {
-	#[serde(rename="?")] position: Position,
+	#[serde(rename="?")] pub position: Position,
 	#[serde(rename="1?")] pub font: Font,
 	color: Option<Color>,
 }
@@ -190,29 +190,34 @@ pub struct PrintStyle {
 #[derive(Debug, Deserialize)]#[serde(rename="print-style-align",rename_all="kebab-case")]
 pub struct PrintStyleAlign {
 	#[serde(rename="?")]
-	print_style: PrintStyle,
-	valign: Option<VAlign>,
+	pub print_style: PrintStyle,
+	pub valign: Option<VAlign>,
 }
 
-#[derive(Debug, Deserialize)]#[serde(rename_all="lowercase")]
-enum Justify { Left, Center, Right }
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="lowercase")]
+pub enum Justify { Left, Center, Right }
 
-#[derive(Debug, Deserialize)]#[serde(rename_all="lowercase")]
-enum VAlign { Top, Middle, Bottom, Baseline }
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="lowercase")]
+pub enum VAlign { Top, Middle, Bottom, Baseline }
 
 #[derive(Debug, Deserialize)]#[serde(rename="formatted-text",rename_all="kebab-case")]
 pub struct FormattedText {
-	justify: Option<Justify>,
+	pub justify: Option<Justify>,
 	#[serde(rename="?")]
-	print_style_align: PrintStyleAlign,
+	pub print_style_align: PrintStyleAlign,
 	#[serde(rename="$")]
 	pub content: String,
 }
 
+fn first() -> u16 { 1 }
 #[derive(Debug, Deserialize)]#[serde(rename="credit",rename_all="kebab-case")]
 pub struct Credit {
-	page: u16,
-	credit_words: FormattedText,
+	#[serde(default="first")]
+	pub page: u16,
+	#[serde(rename="credit-type*")]
+	credit_type: Vec<String>,
+	#[serde(rename="credit-words*")]
+	pub credit_words: Vec<FormattedText>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="virtual-instrument",rename_all="kebab-case")]
@@ -293,13 +298,13 @@ pub struct PartDisplay {
 
 #[derive(Debug, Deserialize)]#[serde(rename="score-part",rename_all="kebab-case")]
 pub struct ScorePart {
-	id: String,
+	pub id: String,
 	identification: Option<Identification>,
 	#[serde(rename="part-link*")]
 	part_link: Vec<PartLink>,
-	part_name: String,
+	pub part_name: String,
 	part_name_display: Option<PartDisplay>,
-	part_abbreviation: Option<String>,
+	pub part_abbreviation: Option<String>,
 	part_abbreviation_display: Option<PartDisplay>,
 	#[serde(rename="score-instrument*")]
 	score_instrument: Vec<ScoreInstrument>,
@@ -308,7 +313,7 @@ pub struct ScorePart {
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="part-list",rename_all="kebab-case")]
-enum PartGroupOrScorePart {
+pub enum PartGroupOrScorePart {
 	PartGroup(PartGroup),
 	ScorePart(ScorePart),
 }
@@ -317,29 +322,29 @@ enum PartGroupOrScorePart {
 pub struct PartList {
 	#[serde(rename="part-group*")]
 	start: Vec<PartGroup>,
-	score_part: ScorePart,
+	pub score_part: ScorePart,
 	#[serde(rename="*")]
-	part_group_score_part: Vec<PartGroupOrScorePart>,
+	pub part_group_score_part: Vec<PartGroupOrScorePart>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="system-margins",rename_all="kebab-case")]
 pub struct SystemMargins {
-	left_margin: f32,
-	right_margin: f32,
+	pub left_margin: f32,
+	pub right_margin: f32,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="system-layout",rename_all="kebab-case")]
 pub struct SystemLayout {
-	system_margins: SystemMargins,
-	system_distance: Option<f32>,
-	top_system_distance: Option<f32>,
+	pub system_margins: SystemMargins,
+	pub system_distance: Option<f32>,
+	pub top_system_distance: Option<f32>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="staff-layout",rename_all="kebab-case")]
 pub struct StaffLayout {
 	#[serde(rename="number")]
 	staff: Option<Staff>,
-	staff_distance: Option<f32>,
+	pub staff_distance: Option<f32>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="measure-distance")]
@@ -356,9 +361,9 @@ pub struct MeasureLayout {
 #[derive(Debug, Deserialize)]#[serde(rename="print",rename_all="kebab-case")]
 pub struct Print {
 	// print-attributes
-	new_system: Option<bool>,
-	new_page: Option<bool>,
-	system_layout: Option<SystemLayout>,
+	pub new_system: Option<bool>,
+	pub new_page: Option<bool>,
+	pub system_layout: Option<SystemLayout>,
 	#[serde(rename="staff-layout*")]
 	staff_layout: Vec<StaffLayout>,
 	measure_layout: Option<MeasureLayout>,
@@ -366,35 +371,48 @@ pub struct Print {
 
 #[derive(Debug, Deserialize)]#[serde(rename="cancel",rename_all="kebab-case")]
 pub struct Cancel {
+	#[serde(rename="$")]
 	pub fifths: i8,
-	location: /*left,right,before-barline*/Option<String>,
+	pub location: Option<CancelLocation>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="kebab-case")]
+pub enum CancelLocation { Left, Right, BeforeBarline }
+
+#[derive(Debug, Deserialize)]#[serde(rename_all="kebab-case")]
+pub enum NonTraditionalKey {
+	KeyStep(Step),
+	KeyAlter(/*semitones*/f32),
+	KeyAccidental(String),
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="key",rename_all="kebab-case")]
 pub struct Key {
-	color: Option<Color>,
-	cancel: Option<Cancel>,
-	pub fifths: i8,
+	pub color: Option<Color>,
+	pub cancel: Option<Cancel>,
+	pub fifths: /*traditional*/Option<i8>,
 	mode: Option<String>,
+	#[serde(rename="*")]
+	pub non_traditional: Vec<NonTraditionalKey>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="time",rename_all="kebab-case")]
 pub struct Time {
-	color: Option<Color>,
+	pub color: Option<Color>,
 	pub beats: u8,
 	pub beat_type: u8,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Clone, Copy)]#[serde(rename=/*"clef-sign"*/"sign")]
-pub enum ClefSign { G, F }
+pub enum ClefSign { G, F, C }
 
 #[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename="clef",rename_all="kebab-case")]
 pub struct Clef {
-	color: Option<Color>,
+	pub color: Option<Color>,
 	#[serde(rename="number")]
 	pub(crate) staff: Staff,
 	pub sign: ClefSign,
-	line: /*2-5*/Option<u8>,
+	pub line: /*1-5*/Option<u8>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="staff-details",rename_all="kebab-case")]
@@ -416,24 +434,42 @@ pub struct Attributes {
 	pub staff_details: Vec<StaffDetails>,
 }
 
+#[derive(Debug, Deserialize)]#[serde(rename_all="kebab-case")]
+pub enum MetronomeData { BeatUnit(NoteTypeValue), BeatUnitDot, PerMinute(String) }
+
 #[derive(Debug, Deserialize)]#[serde(rename="metronome",rename_all="kebab-case")]
 pub struct Metronome {
-	beat_unit: /*quarter*/String,
-	per_minute: u16,
-	#[serde(rename="?")] print_style: PrintStyle,
-	parentheses: Option<bool>,
+	#[serde(rename="")]
+	pub content: /*beat-unit, beat-unit-dot*, (per-minute | beat-unit, beat-unit-dot*)*/Vec<MetronomeData>,
+	#[serde(rename="?")] pub print_style: PrintStyle,
+	pub parentheses: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="lowercase")]
+pub enum PedalType { Start, Stop, Sostenuto, Change, Continue, Discontinue, Resume }
+
+#[derive(Debug, Deserialize)]#[serde(rename="pedal",rename_all="kebab-case")]
+pub struct Pedal {
+	pub r#type: PedalType,
+	pub number: Option<u8>,
+	pub line: Option<bool>,
+	pub sign: Option<bool>,
+	#[serde(rename="?")] pub print_style_align: PrintStyleAlign,
 }
 
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="lowercase")]
+pub enum UpDownStopContinue { Up, Down, Stop, Continue }
+
 fn eight() -> u8 { 8 }
 #[derive(Debug, Deserialize)]#[serde(rename="octave-shift",rename_all="kebab-case")]
 pub struct OctaveShift {
-	r#type: /*up,down,stop,continue*/String,
-	number: Option<u8>,
-	#[serde(default="eight")] size: u8,
-	#[serde(rename="?")] print_style: PrintStyle,
+	pub r#type: UpDownStopContinue,
+	pub number: Option<u8>,
+	#[serde(default="eight")] pub size: /*8,15,22*/u8,
+	#[serde(rename="?")] pub print_style: PrintStyle,
 }
 
-#[derive(Debug, Deserialize)]#[serde(rename_all="kebab-case")]#[allow(non_camel_case_types)]
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="kebab-case")]#[allow(non_camel_case_types)]
 pub enum DynamicText { pppppp,ppppp,pppp,ppp,pp,p,mp,mf,f,ff,fff,ffff,fffff,ffffff, sf,sfp,sfpp,fp,rf,rfz,sfz,sffz,fz,n,pf,sfzp }
 
 #[derive(Debug, Deserialize)]#[serde(rename="dynamics",rename_all="kebab-case")]
@@ -442,15 +478,17 @@ pub struct Dynamics {
 	#[serde(rename="")] pub text: DynamicText,
 }
 
-#[derive(Debug, Deserialize)]#[serde(rename="type",rename_all="kebab-case")]
-enum WedgeType { Crescendo, Diminuendo, Stop, Continue }
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename="type",rename_all="kebab-case")]
+pub enum WedgeType { Crescendo, Diminuendo, Stop, Continue }
 
 #[derive(Debug, Deserialize)]#[serde(rename="wedge",rename_all="kebab-case")]
 pub struct Wedge {
-	r#type: WedgeType,
-	number: Option<u8>,
-	#[serde(rename="?")] position: Option<Position>,
-	color: Option<Color>,
+	pub r#type: WedgeType,
+	pub number: Option<u8>,
+	pub spread: Option<f32>,
+	pub niente: Option<bool>,
+	#[serde(rename="?")] pub position: Option<Position>,
+	pub color: Option<Color>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename_all="kebab-case")]
@@ -458,7 +496,11 @@ pub enum DirectionTypeData {
 	Metronome(Metronome),
 	OctaveShift(OctaveShift),
 	Words(FormattedText),
+	Rehearsal(FormattedText),
+	Segno(PrintStyleAlign),
+	Coda(PrintStyleAlign),
 	Dynamics(Dynamics),
+	Pedal(Pedal),
 	Wedge(Wedge),
 }
 
@@ -476,6 +518,12 @@ pub struct DirectionType {
 pub struct Sound {
 	dynamics: Option<uf32>,
 	tempo: Option<uf32>,
+	pub dacapo: Option<bool>,
+	pub segno: Option<String>,
+	pub dalsegno: Option<String>,
+	pub coda: Option<String>,
+	pub tocoda: Option<String>,
+	pub fine: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="offset",rename_all="kebab-case")]
@@ -491,9 +539,9 @@ pub struct Direction {
 	pub direction_type: Vec<DirectionType>,
 	offset: Option<Offset>,
 	voice: Option<String>,
-	staff: Option<Staff>,
-	sound: Option<Sound>,
-	placement: /*above,below*/Option<String>,	
+	pub staff: Option<Staff>,
+	pub sound: Option<Sound>,
+	pub placement: Option<Placement>,
 }
 
 #[derive(Debug, Deserialize, PartialEq, PartialOrd, Clone, Copy)]#[serde(rename_all="kebab-case")]
@@ -524,13 +572,13 @@ pub struct Tie {
 	//time-only: Option
 }
 
-#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="kebab-case")]
+#[derive(Debug, Deserialize, PartialEq, Clone, Copy)]#[serde(rename_all="kebab-case")]
 pub enum StemDirection { Down, Up, Double, None }
 
 #[derive(Debug, Deserialize)]#[serde(rename="stem",rename_all="kebab-case")]
 pub struct Stem {
 	#[serde(rename="$")]
-	value: StemDirection,
+	pub value: StemDirection,
 	//y-position
 	//color
 }
@@ -547,6 +595,8 @@ pub struct Pitch {
 
 #[derive(Debug, Deserialize)]#[serde(rename="rest",rename_all="kebab-case")]
 pub struct Rest {
+	pub display_step: Option<Step>,
+	pub display_octave: Option<u8>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename_all="kebab-case")]
@@ -557,13 +607,13 @@ pub enum NoteData {
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="beam",rename_all=/*space lowercase*/"kebab-case")]
-enum BeamValue { Begin, Continue, End, #[serde(rename="forward hook")] ForwardHook, #[serde(rename="backward hook")] BackwardHook }
+pub enum BeamValue { Begin, Continue, End, #[serde(rename="forward hook")] ForwardHook, #[serde(rename="backward hook")] BackwardHook }
 
 #[derive(Debug, Deserialize)]#[serde(rename="beam",rename_all="kebab-case")]
 pub struct Beam {
 	#[serde(rename="$")]
-	value: BeamValue,
-	number: /*BeamLevel=1*/Option<u8>,
+	pub value: BeamValue,
+	pub number: /*BeamLevel=1*/Option<u8>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename_all="kebab-case")]
@@ -582,35 +632,71 @@ pub struct Slur {
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename_all="kebab-case")]
-pub enum ArticulationData { Accent, StrongAccent, Staccato, Tenuto, DetachedLegato, Staccatissimo, Spiccato, Scoop, Plop, Doit, Falloff,
-	BreathMark, Caesura, Stress, Unstress, SoftAccent }
+pub enum ArticulationData { Accent(EmptyPlacement), StrongAccent(EmptyPlacement), Staccato(EmptyPlacement), Tenuto(EmptyPlacement), DetachedLegato(EmptyPlacement),
+	Staccatissimo(EmptyPlacement), Spiccato(EmptyPlacement), Scoop(EmptyPlacement), Plop(EmptyPlacement), Doit(EmptyPlacement), Falloff(EmptyPlacement),
+	BreathMark(BreathMark), Caesura(Caesura), Stress(EmptyPlacement), Unstress(EmptyPlacement), SoftAccent(EmptyPlacement) }
+
+#[derive(Debug, Deserialize)]#[serde(rename="breath-mark",rename_all="kebab-case")]
+pub struct BreathMark {
+	#[serde(rename="$")]
+	value: /*comma,tick,upbow,salzedo*/Option<String>,
+	#[serde(rename="?")]
+	print_style: PrintStyle,
+	pub placement: Option<Placement>,
+}
+type Caesura = /*normal,thick,short,curved,single*/BreathMark;
 
 #[derive(Debug, Deserialize)]#[serde(rename="articulations",rename_all="kebab-case")]
 pub struct Articulations {
 	#[serde(rename="")]
-	content: Vec<ArticulationData>,
+	pub content: Vec<ArticulationData>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="tremolo",rename_all="kebab-case")]
 pub struct Tremolo {
-	r#type: /*single,start*/Option<String>,
+	pub r#type: /*single,start,stop,unmeasured*/Option<String>,
 	#[serde(rename="$")]
-	marks: /*0-8*/u8,
+	pub marks: /*0-8*/u8,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="lowercase")]
+pub enum StartStopContinue { Start, Stop, Continue }
+
+#[derive(Debug, Deserialize)]#[serde(rename="wavy-line",rename_all="kebab-case")]
+pub struct WavyLine {
+	pub r#type: StartStopContinue,
+	pub number: Option<u8>,
+	pub placement: Option<Placement>,
+}
+
+#[derive(Debug, Deserialize)]#[serde(rename="accidental-mark",rename_all="kebab-case")]
+pub struct AccidentalMark {
+	#[serde(rename="$")]
+	pub value: Accidental,
+	#[serde(rename="?")]
+	print_style: PrintStyle,
+	pub placement: Option<Placement>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename_all="kebab-case")]
-pub enum OrnamentData { Tremolo(Tremolo) }
+pub enum OrnamentData { TrillMark(EmptyPlacement), Turn(EmptyPlacement), InvertedTurn(EmptyPlacement), Mordent(EmptyPlacement), InvertedMordent(EmptyPlacement), Schleifer(EmptyPlacement),
+	WavyLine(WavyLine), Tremolo(Tremolo), AccidentalMark(AccidentalMark) }
 
 #[derive(Debug, Deserialize)]#[serde(rename="ornaments",rename_all="kebab-case")]
 pub struct Ornaments {
 	#[serde(rename="")]
-	content: Vec<OrnamentData>,
+	pub content: Vec<OrnamentData>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="fingering",rename_all="kebab-case")]
 pub struct Fingering {
 	#[serde(rename="$")]
-	finger: /*1-5*/u8,
+	pub finger: /*1-5*/u8,
+	pub substitution: Option<bool>,
+	pub alternate: Option<bool>,
+	#[serde(rename="?")]
+	print_style: PrintStyle,
+	pub placement: Option<Placement>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename_all="kebab-case")]
@@ -619,7 +705,78 @@ pub enum TechnicalData { Fingering(Fingering) }
 #[derive(Debug, Deserialize)]#[serde(rename="technical",rename_all="kebab-case")]
 pub struct Technical {
 	#[serde(rename="")]
-	content: Vec<TechnicalData>,
+	pub content: Vec<TechnicalData>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="lowercase")]
+pub enum Placement { Above, Below }
+
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="kebab-case")]
+pub enum FermataShape { Normal, Angled, Square, DoubleAngled, DoubleSquare, DoubleDot, HalfCurve, Curlew }
+
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="lowercase")]
+pub enum UprightInverted { Upright, Inverted }
+
+#[derive(Debug, Deserialize)]#[serde(rename="fermata",rename_all="kebab-case")]
+pub struct Fermata {
+	#[serde(rename="$")]
+	pub shape: Option<FermataShape>,
+	pub r#type: Option<UprightInverted>,
+	#[serde(rename="?")]
+	print_style: PrintStyle,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="lowercase")]
+pub enum UpDown { Up, Down }
+
+#[derive(Debug, Deserialize)]#[serde(rename="arpeggiate",rename_all="kebab-case")]
+pub struct Arpeggiate {
+	pub number: Option<u8>,
+	pub direction: Option<UpDown>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="lowercase")]
+pub enum TopBottom { Top, Bottom }
+
+#[derive(Debug, Deserialize)]#[serde(rename="non-arpeggiate",rename_all="kebab-case")]
+pub struct NonArpeggiate {
+	pub r#type: TopBottom,
+	pub number: Option<u8>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="lowercase")]
+pub enum LineType { Solid, Dashed, Dotted, Wavy }
+
+#[derive(Debug, Deserialize)]#[serde(rename="glissando",rename_all="kebab-case")]
+pub struct Glissando {
+	pub r#type: StartStop,
+	pub number: Option<u8>,
+	pub line_type: Option<LineType>,
+	#[serde(rename="$")]
+	text: Option<String>,
+}
+pub type Slide = Glissando;
+
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="lowercase")]
+pub enum ShowTuplet { Actual, Both, None }
+
+#[derive(Debug, Deserialize)]#[serde(rename="tuplet-portion",rename_all="kebab-case")]
+pub struct TupletPortion {
+	pub tuplet_number: Option<u8>,
+	tuplet_type: Option<NoteType>,
+	#[serde(rename="tuplet-dot*")]
+	tuplet_dot: Vec<EmptyPlacement>,
+}
+
+#[derive(Debug, Deserialize)]#[serde(rename="tuplet",rename_all="kebab-case")]
+pub struct Tuplet {
+	pub r#type: StartStop,
+	pub number: Option<u8>,
+	pub bracket: Option<bool>,
+	pub show_number: Option<ShowTuplet>,
+	pub placement: Option<Placement>,
+	pub tuplet_actual: Option<TupletPortion>,
+	pub tuplet_normal: Option<TupletPortion>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename_all="kebab-case")]
@@ -627,32 +784,31 @@ pub enum Notation {
 	Tied(Tied),
 	Articulations(Articulations),
 	Slur(Slur),
-		/*slur
-		tuplet
-		glissando
-		slide*/
+	Tuplet(Tuplet),
+	Glissando(Glissando),
+	Slide(Slide),
 	Ornaments(Ornaments),
 	Technical(Technical),
-		/*dynamics
-		fermata
-		arpeggiate
-		non-arpeggiate
-		accidental-mark
-		other-notation*/
+		//dynamics
+	Fermata(Fermata),
+	Arpeggiate(Arpeggiate),
+	NonArpeggiate(NonArpeggiate),
+	AccidentalMark(AccidentalMark),
+		//other-notation
 
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="notations",rename_all="kebab-case")]
 pub struct Notations {
 	#[serde(rename="*")]
-	content: Vec<Notation>
+	pub content: Vec<Notation>
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename_all="kebab-case")]
 pub struct EmptyPlacement {
 	#[serde(rename="?"/*0*/)]
 	print_style: PrintStyle,
-	//placement
+	pub placement: Option<Placement>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="time-modification",rename_all="kebab-case")]
@@ -661,36 +817,42 @@ pub struct TimeModification {
 	pub normal_notes: u8,
 }
 
-#[derive(Debug, Deserialize)]#[serde(rename="accidental",rename_all="lowercase")]
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename="accidental",rename_all="lowercase")]
 pub enum Accidental { Flat, Natural, Sharp }
 
-#[derive(Debug, Deserialize)]#[serde(rename="grace",rename_all="lowercase")]
-pub struct Grace {}
+#[derive(Debug, Deserialize)]#[serde(rename="grace",rename_all="kebab-case")]
+pub struct Grace {
+	pub slash: Option<bool>,
+	steal_time_previous: Option</*%*/uf32>,
+	steal_time_following: Option</*%*/uf32>,
+	make_time: Option</*divisions*/uf32>,
+}
 
 #[derive(Debug, Deserialize)]#[serde(rename="note",rename_all="kebab-case")]
 pub struct Note {
-	#[serde(rename="?")] position: Position,
+	#[serde(rename="?")] pub position: Position,
 	#[serde(rename="1?")] font: Font,
-	color: Option<Color>,
+	pub color: Option<Color>,
 	pub duration: Option<u32>,
 	#[serde(rename="instrument*")]
 	instruments: Vec<String>,
-	voice: Option<u8>,
+	pub voice: Option<u8>,
 	pub r#type: Option<NoteType>,
-	accidental: Option<Accidental>,
-	time_modification: Option<TimeModification>,
+	pub accidental: Option<Accidental>,
+	pub time_modification: Option<TimeModification>,
 	#[serde(rename="dot*")]
 	dot: Vec<EmptyPlacement>,
 	#[serde(rename="tie{0,2}")]
 	ties: Vec<Tie>,
 	#[serde(rename="beam{0,8}")]
-	beams: Vec<Beam>,
+	pub beams: Vec<Beam>,
 	#[serde(rename="notations*")]
-	notations: Vec<Notations>,
+	pub notations: Vec<Notations>,
 	pub staff: Option<Staff>,
 	pub stem: Option<Stem>,
 	pub chord: Option<()>,
 	pub grace: Option<Grace>,
+	pub cue: Option<()>,
 	#[serde(rename="")]
 	pub content: NoteData,
 	id: Option<String>,
@@ -706,30 +868,44 @@ pub struct Forward {
 	pub duration: u32,
 }
 
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="lowercase")]
+pub enum StartStopDiscontinue { Start, Stop, Discontinue }
+
 #[derive(Debug, Deserialize)]#[serde(rename="ending",rename_all="kebab-case")]
 pub struct Ending {
-	number: u8,
-	r#type: /*start,stop,discontinue*/String,
+	pub number: /*1, 2*/String,
+	pub r#type: StartStopDiscontinue,
+	#[serde(rename="$")]
+	pub text: Option<String>,
 	#[serde(rename="?")]
 	print_style: PrintStyle,
 }
 
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="lowercase")]
+pub enum BackwardForward { Backward, Forward }
+
 #[derive(Debug, Deserialize)]#[serde(rename="repeat",rename_all="kebab-case")]
 pub struct Repeat {
-	direction: /*backward,forward*/String,
+	pub direction: BackwardForward,
+	pub times: Option<u8>,
 }
 
-#[derive(Debug, Deserialize)]#[serde(rename_all="lowercase")]
-enum RightLeftMiddle { Right, Left, Middle }
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="lowercase")]
+pub enum RightLeftMiddle { Right, Left, Middle }
 fn right() -> RightLeftMiddle { RightLeftMiddle::Right }
 
+#[derive(Debug, Deserialize, Clone, Copy)]#[serde(rename_all="kebab-case")]
+pub enum BarStyle { Regular, Dotted, Dashed, Heavy, LightLight, LightHeavy, HeavyLight, HeavyHeavy, Tick, Short, None }
+
 #[derive(Debug, Deserialize)]#[serde(rename="barline",rename_all="kebab-case")]
 pub struct Barline {
-	repeat: Option<Repeat>,
+	pub repeat: Option<Repeat>,
 	#[serde(default="right")]
-	location: RightLeftMiddle,
-	bar_style: /*enum*/Option<String>,
-	ending: Option<Ending>,
+	pub location: RightLeftMiddle,
+	pub bar_style: Option<BarStyle>,
+	pub ending: Option<Ending>,
+	#[serde(rename="fermata*")]
+	pub fermata: Vec<Fermata>,
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename_all="kebab-case")]
@@ -740,12 +916,14 @@ pub enum MusicData {
 	Print(Print),
 	Attributes(Attributes),
 	Direction(Direction),
-	Barline(Barline)
+	Barline(Barline),
+	Sound(Sound),
 }
 
 #[derive(Debug, Deserialize)]#[serde(rename="measure",rename_all="kebab-case")]
 pub struct Measure {
-	number: u32,
+	pub number: u32,
+	pub implicit: Option<bool>, // Pickup or split measure, not counted
 	width: uf32,
 	#[serde(rename="*")]
 	pub music_data: Vec<MusicData>,
@@ -753,7 +931,7 @@ pub struct Measure {
 
 #[derive(Debug, Deserialize)]#[serde(rename="part",rename_all="kebab-case")]
 pub struct Part {
-	id: String,
+	pub id: String,
 	#[serde(rename="measure+")]
 	pub measures: Vec<Measure>
 }
@@ -761,12 +939,12 @@ pub struct Part {
 #[derive(Debug, Deserialize)]#[serde(rename="score-partwise",rename_all="kebab-case")]
 pub struct ScorePartwise {
 	version: Option<String>,
-	work: Option<Work>,
-	identification: Identification,
-	defaults: Defaults,
+	pub work: Option<Work>,
+	pub identification: Identification,
+	pub defaults: Defaults,
 	#[serde(rename="credit*")]
-	credits: Vec<Credit>,
-	part_list: PartList,
+	pub credits: Vec<Credit>,
+	pub part_list: PartList,
     #[serde(rename="part+")]
 	pub parts : Vec<Part>
 }
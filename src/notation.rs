@@ -0,0 +1,68 @@
+use {vector::MinMax, ui::graphic::vertical, crate::{music_xml::{Note, Notation, Fermata, FermataShape, UprightInverted, Arpeggiate, UpDown, Glissando, LineType, StartStop, AccidentalMark, Placement}, font::SMuFL::{fermata, arpeggio, accidental}, sheet::horizontal, spanner::{Event, Point, Kind}, measure::MeasureLayoutContext}};
+
+pub fn fermata(&Fermata{shape, r#type, ..}: &Fermata) -> char {
+	use {FermataShape::*, fermata::*};
+	let glyph = match shape.unwrap_or(Normal) { Normal=>normal, Angled=>short, Square=>long, DoubleAngled=>very_short, DoubleSquare=>very_long, DoubleDot=>long_henze, HalfCurve=>short_henze, Curlew=>return curlew };
+	if let Some(UprightInverted::Inverted) = r#type { below(glyph) } else { glyph }
+}
+
+impl MeasureLayoutContext<'_> {
+	// Space left of the chord accidentals for an arpeggio or a non-arpeggiate bracket
+	pub fn arpeggio_width(&self, chord: &[&Note]) -> i32 {
+		if chord.iter().flat_map(|note| note.notations()).any(|notation| matches!(notation, Notation::Arpeggiate(_)|Notation::NonArpeggiate(_))) { self.sheet.advance(arpeggio::segment) + (self.space()/4) as i32 } else { 0 }
+	}
+	// Stacks accidental marks then fermatas beyond the edges (upright above, inverted below). Draws arpeggios left of the accidentals, and starts and stops glissandi and slides from heads (x, step)
+	pub fn notations(&mut self, staff: usize, chord: &[&Note], heads: &[(i32, i8, &Note)], edges: MinMax<i8>, MinMax{min: left, max: right}: MinMax<i32>, accidentals: i32) {
+		let notations = || chord.iter().flat_map(|note| note.notations());
+		let (mut above, mut below) = (edges.max, edges.min);
+		for notation in notations() {
+			if let Notation::AccidentalMark(AccidentalMark{value, placement, ..}) = notation {
+				let glyph = accidental::from(*value);
+				if let Some(Placement::Below) = placement { self.push_stacked((left+right)/2, staff, &mut below, false, &[glyph], 0.6); } else { self.push_stacked((left+right)/2, staff, &mut above, true, &[glyph], 0.6); }
+			}
+		}
+		for notation in notations() {
+			if let Notation::Fermata(f) = notation {
+				if let Some(UprightInverted::Inverted) = f.r#type { self.push_stacked((left+right)/2, staff, &mut below, false, &[fermata(f)], 1.); } else { self.push_stacked((left+right)/2, staff, &mut above, true, &[fermata(f)], 1.); }
+			}
+		}
+
+		let space = self.space() as i32;
+		let x = accidentals - self.arpeggio_width(chord);
+		if let Some(MinMax{min: bottom, max: top}) = heads.iter().map(|&(_, step, _)| MinMax{min: step, max: step}).reduce(MinMax::minmax) {
+			let (y0, y1) = (self.y(staff, bottom-1), self.y(staff, top+1));
+			if let Some(Arpeggiate{direction, ..}) = notations().find_map(|notation| if let Notation::Arpeggiate(arpeggiate) = notation { Some(arpeggiate) } else { None }) { // Wavy line segments from the bottom
+				let sheet = self.sheet;
+				let bbox = |glyph| sheet.bbox(glyph);
+				let height = |glyph| { let bbox = bbox(glyph); (bbox.y_max - bbox.y_min) as i32 };
+				let segments = (y0 - y1 + height(arpeggio::segment) - 1) / height(arpeggio::segment);
+				let glyphs = matches!(direction, Some(UpDown::Down)).then(|| arpeggio::down).into_iter().chain(std::iter::repeat(arpeggio::segment).take(segments as usize)).chain(matches!(direction, Some(UpDown::Up)).then(|| arpeggio::up));
+				let mut y = y0;
+				for glyph in glyphs {
+					self.push_glyph(x as u32, staff, bottom-1, bbox(glyph).y_min as i32 - (y0 - y), glyph);
+					y -= height(glyph);
+				}
+			} else if notations().any(|notation| matches!(notation, Notation::NonArpeggiate(_))) { // Bracket
+				let thickness = self.sheet.engraving_defaults.tuplet_bracket_thickness;
+				self.measure.graphic.rects.extend([vertical(x, thickness, y1, y0), horizontal(y1, thickness, x, x + space/4), horizontal(y0, thickness, x, x + space/4)]);
+			}
+		}
+
+		let mut events = Vec::new();
+		for &(_, step, note) in heads {
+			for notation in note.notations() {
+				let (Glissando{r#type, number, ..}, kind) = match notation { // Distinct kinds so that a glissando stop does not close a slide
+					Notation::Glissando(glissando) => (glissando, Kind::Glissando(glissando.line_type.unwrap_or(LineType::Wavy))),
+					Notation::Slide(slide) => (slide, Kind::Slide(slide.line_type.unwrap_or(LineType::Solid))),
+					_ => continue
+				};
+				events.push(match r#type {
+					StartStop::Start => Event::Start(Point{kind, number: *number, staff, step, x: right + space/4}),
+					StartStop::Stop => Event::Stop(Point{kind, number: *number, staff, step, x: accidentals - space/4}),
+				});
+			}
+		}
+		events.sort_by_key(|event| matches!(event, Event::Start(_))); // Stops the incoming line before starting the outgoing one
+		self.spanners.extend(events);
+	}
+}
@@ -0,0 +1,42 @@
+use {vector::MinMax, crate::{music_xml::{Note, Notation, Ornaments, OrnamentData, WavyLine, Tremolo, AccidentalMark, StartStopContinue, Placement}, font::SMuFL::{ornament, accidental, tremolo}, spanner::{Event, Point, Kind}, measure::MeasureLayoutContext}};
+impl MeasureLayoutContext<'_> {
+	// Stacks ornaments outside the staff and articulations, above unless placed below. Starts and stops trill extensions. Strokes tremolos (or a buzz roll) through stems (x, step). Returns the outer edges
+	pub fn ornaments(&mut self, staff: usize, chord: &[&Note], edges: MinMax<i8>, MinMax{min: left, max: right}: MinMax<i32>, (stem, stem_step): (i32, i8)) -> MinMax<i8> {
+		let ornaments = chord.iter().flat_map(|note| note.notations()).filter_map(|notation| if let Notation::Ornaments(Ornaments{content}) = notation { Some(content) } else { None }).flatten();
+		let (mut above, mut below) = (std::cmp::max(edges.max, 8) + 1, std::cmp::min(edges.min, 0) - 1); // Outer edges
+		let mut trill = None; // Extension starts after the trill mark
+		for ornament in ornaments {
+			let (glyph, placement, scale) = match ornament {
+				OrnamentData::TrillMark(p) => (ornament::trill, p.placement, 1.), OrnamentData::Turn(p) => (ornament::turn, p.placement, 1.), OrnamentData::InvertedTurn(p) => (ornament::inverted_turn, p.placement, 1.),
+				OrnamentData::Mordent(p) => (ornament::mordent, p.placement, 1.), OrnamentData::InvertedMordent(p) => (ornament::short_trill, p.placement, 1.), OrnamentData::Schleifer(p) => (ornament::schleifer, p.placement, 1.),
+				OrnamentData::AccidentalMark(AccidentalMark{value, placement, ..}) => (accidental::from(*value), *placement, 0.6),
+				OrnamentData::WavyLine(WavyLine{r#type, number, ..}) => {
+					let (x, step) = trill.unwrap_or((left, above + 1));
+					match r#type {
+						StartStopContinue::Start => self.spanners.push(Event::Start(Point{kind: Kind::WavyLine, number: *number, staff, step, x})),
+						StartStopContinue::Stop => self.spanners.push(Event::Stop(Point{kind: Kind::WavyLine, number: *number, staff, step, x: right})),
+						StartStopContinue::Continue => {}
+					}
+					continue;
+				}
+				&OrnamentData::Tremolo(Tremolo{ref r#type, marks}) => {
+					let point = Point{kind: Kind::Tremolo(marks), number: None, staff, step: stem_step, x: stem};
+					match r#type.as_deref() {
+						Some("start") => self.spanners.push(Event::Start(point)),
+						Some("stop") => self.spanners.push(Event::Stop(point)),
+						Some("unmeasured") => self.push_glyph((stem - self.sheet.advance(tremolo::buzz_roll)/2) as u32, staff, stem_step, self.sheet.center(tremolo::buzz_roll), tremolo::buzz_roll),
+						_ if marks > 0 => { // Single note
+							let glyph = tremolo::from(marks);
+							self.push_glyph((stem - self.sheet.advance(glyph)/2) as u32, staff, stem_step, self.sheet.center(glyph), glyph);
+						}
+						_ => {}
+					}
+					continue;
+				}
+			};
+			let (x, center) = if let Some(Placement::Below) = placement { self.push_stacked((left+right)/2, staff, &mut below, false, &[glyph], scale) } else { self.push_stacked((left+right)/2, staff, &mut above, true, &[glyph], scale) };
+			if let OrnamentData::TrillMark(_) = ornament { trill = Some((x, center)); }
+		}
+		MinMax{min: below, max: above}
+	}
+}
@@ -0,0 +1,21 @@
+use {vector::MinMax, crate::{music_xml::{Note, NoteType, NoteTypeValue, NoteData, Rest, Pitch}, font::SMuFL::rest, staff::{Staff, Index}, measure::MeasureLayoutContext}};
+impl MeasureLayoutContext<'_> { pub fn rest(&mut self, staves: &[Staff], note: &Note) {
+	let Note{staff: Some(staff), r#type, content: NoteData::Rest(Rest{display_step, display_octave}), ..} = note else { return };
+	let staff = staves.index(staff);
+	let value = r#type.as_ref().map(|&NoteType{value}| value).unwrap_or(NoteTypeValue::Whole); // Measure rest
+	let step = if let &Some(step) = display_step { staff.step(&Pitch{step, alter: None, octave: *display_octave}) } else {
+		(if value == NoteTypeValue::Whole { 6 } else { 4 }) // Whole rest hangs from the fourth line
+		+ if !staff.polyphonic { 0 } else if let crate::music_xml::StemDirection::Up = note.voice_stem_direction() { 4 } else { -4 } // Displaced in the direction of the voice stems
+	};
+	let glyph = rest::from(NoteTypeValue::Maxima as u32 - value as u32);
+	let x = self.sheet.position(::xy::xy{x: self.x as i32, y: 0}, ::xy::xy{x: 0, y: 0}, &note.position).x.max(0) as u32; // Not left of the measure (negative default-x)
+	let x = self.onset(self.t, x, self.sheet.advance(glyph) as u32); // Aligned with the heads of the other voices
+	self.measure.scale = self.sheet.note_scale(note); // Cue
+	self.set_color(note.color);
+	self.push_glyph(x, staff.index, step, 0, glyph);
+	let right = x as i32 + self.sheet.advance(glyph);
+	self.notations(staff.index, &[note], &[], MinMax{min: std::cmp::min(step-2, 0)-1, max: std::cmp::max(step+2, 8)+1}, MinMax{min: x as i32, max: right}, x as i32); // Fermatas
+	self.measure.scale = 1.;
+	self.set_color(None);
+	self.tuplet(&[note], staff.index, MinMax{min: x as i32, max: right}, MinMax{min: step-2, max: step+2}, matches!(note.voice_stem_direction(), crate::music_xml::StemDirection::Up), None); // Tuplets starting or stopping on a rest
+}}
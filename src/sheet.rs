@@ -1,5 +1,15 @@
 #![allow(non_upper_case_globals)]
-use {derive_more::Deref, ttf_parser::Face, crate::{Font, font::{SMuFont, SMuFL::EngravingDefaults}}, ::xy::{xy,Rect}};
+use {derive_more::Deref, ttf_parser::Face, crate::{Font, font::{SMuFont, SMuFL::EngravingDefaults}, music_xml::{self, Position, Defaults, Scaling, StaffLayout, Appearance, LineWidth, LineWidthType, NoteSize, NoteSizeType, Note}}, ::xy::{xy,Rect}};
+
+pub fn horizontal(y: i32, dy: u32, x0: i32, x1: i32) -> Rect { Rect{ min: xy{ y: y-(dy/2) as i32, x: x0 }, max: xy{ y: y+(dy/2) as i32, x: x1 } } }
+
+#[derive(Clone, Copy)] pub enum MeasureNumbering { None, System, Every(u32) }
+#[derive(Clone, Copy)] pub struct Options {
+	pub positions: bool, // Honours encoded default and relative positions
+	pub measure_numbering: MeasureNumbering,
+	pub boxed_measure_numbers: bool,
+}
+impl Default for Options { fn default() -> Self { Self{positions: false, measure_numbering: MeasureNumbering::System, boxed_measure_numbers: false} } }
 
 #[derive(Deref)] 
 pub struct Sheet {
@@ -7,7 +17,12 @@ pub struct Sheet {
 	#[deref] pub face: &'static Face<'static>,
 	pub engraving_defaults: EngravingDefaults, 
 	pub staff_height: u32, 
-	pub staff_distance: u32 
+	pub staff_distance: u32,
+	pub grace_size: f32,
+	pub cue_size: f32,
+	pub grace_cue_size: f32,
+	pub millimeters_per_space: f32,
+	pub options: Options,
 }
 impl Sheet {
 	pub fn new_with_face(font: Font, face: &'static Face) -> Self {
@@ -18,19 +33,84 @@ impl Sheet {
 			face,
 			engraving_defaults: Face::engraving_defaults(),
 			staff_height,
-			staff_distance: 7*interval
+			staff_distance: 7*interval,
+			grace_size: 0.6,
+			cue_size: 0.75,
+			grace_cue_size: 0.5,
+			millimeters_per_space: 1.75,
+			options: Default::default(),
 		}
 	}
 	pub fn new(font: Font) -> Self {
 		static face: std::lazy::SyncLazy<ui::font::File<'static>> = std::lazy::SyncLazy::new(|| ui::font::open(std::path::Path::new(&(std::env::var("HOME").unwrap()+"/.local/share/fonts/Bravura.otf"))).unwrap());
 	    Self::new_with_face(font, &face)
     }
+	pub fn with_defaults(mut self, Defaults{scaling, staff_layout, appearance, ..}: &Defaults) -> Self {
+		if let Some(&Scaling{millimeters, tenths}) = scaling.as_ref() { self.millimeters_per_space = millimeters / tenths as f32 * 10.; }
+		for &StaffLayout{staff_distance, ..} in staff_layout { if let Some(distance) = staff_distance { self.staff_distance = self.tenths(distance) as u32 + self.staff_height; } } // From the bottom line of the staff above to the top line
+		if let Some(Appearance{line_width, note_size, ..}) = appearance {
+			for &LineWidth{r#type, tenths} in line_width {
+				let width = self.tenths(tenths) as u32;
+				let defaults = &mut self.engraving_defaults;
+				use LineWidthType::*;
+				match r#type {
+					Staff => defaults.staff_line_thickness = width,
+					Stem => defaults.stem_thickness = width,
+					LightBarline => defaults.thin_barline_thickness = width,
+					HeavyBarline => defaults.thick_barline_thickness = width,
+					Ending => defaults.repeat_ending_line_thickness = width,
+					Wedge => defaults.hairpin_thickness = width,
+					OctaveShift => defaults.octave_line_thickness = width,
+					Pedal => defaults.pedal_line_thickness = width,
+					Beam => defaults.beam_thickness = width,
+					Leger => defaults.leger_line_thickness = width,
+					TupletBracket => defaults.tuplet_bracket_thickness = width,
+					_ => {}
+				}
+			}
+			for &NoteSize{r#type, percents} in note_size {
+				use NoteSizeType::*;
+				match r#type { Grace => self.grace_size = percents/100., Cue => self.cue_size = percents/100., GraceCue => self.grace_cue_size = percents/100., Large => {} }
+			}
+		}
+		self
+	}
+	pub fn with_options(self, options: Options) -> Self { Self{options, ..self} }
+	pub fn advance(&self, glyph: char) -> i32 { self.face.glyph_hor_advance(self.face.glyph_index(glyph).unwrap()).unwrap() as i32 }
+	pub fn bbox(&self, glyph: char) -> ttf_parser::Rect { self.face.glyph_bounding_box(self.face.glyph_index(glyph).unwrap()).unwrap() }
+	pub fn center(&self, glyph: char) -> i32 { let bbox = self.bbox(glyph); (bbox.y_min as i32 + bbox.y_max as i32) / 2 } // Vertical, from the baseline
+	pub fn note_scale(&self, note: &Note) -> f32 {
+		match (note.grace.is_some(), note.cue.is_some()) { (true, true) => self.grace_cue_size, (true, false) => self.grace_size, (false, true) => self.cue_size, (false, false) => 1. }
+	}
+	// MusicXML tenths of interline space to font units
+	pub fn tenths(&self, tenths: f32) -> i32 { (tenths * (self.staff_height/4) as f32 / 10.) as i32 }
+	// Encoded placement in place of the computed one when positions are honoured: default from the origin (default-y up from the top staff line), then relative
+	pub fn position(&self, computed: xy<i32>, origin: xy<i32>, &Position{default_x, default_y, relative_x, relative_y}: &Position) -> xy<i32> {
+		if !self.options.positions { return computed; }
+		xy{x: default_x.map_or(computed.x, |x| origin.x + self.tenths(x)) + self.tenths(relative_x.unwrap_or(0.)), y: default_y.map_or(computed.y, |y| origin.y - self.tenths(y)) - self.tenths(relative_y.unwrap_or(0.))}
+	}
+	// Points to font units at the score scaling
+	pub fn points(&self, points: f32) -> u32 { (points * 25.4 / 72. / self.millimeters_per_space * (self.staff_height/4) as f32) as u32 }
+	// Registered or installed face for the font family, weight and style, or the default text face
+	pub fn text_face(&self, music_xml::Font{font_family, font_style, font_weight, ..}: &music_xml::Font) -> &'static ui::font::File<'static> {
+		let Some(family) = font_family else { return ui::text::default_font()[0] };
+		let key = format!("{family}{}", match (font_weight.as_deref(), font_style.as_deref()) { (Some("bold"), Some("italic")) => "-BoldItalic", (Some("bold"), _) => "-Bold", (_, Some("italic")) => "-Italic", _ => "" });
+		if let Some((_, face)) = self.font.iter().find(|(name,_)| name == &key) { return face; }
+		for name in [&key, family] { for extension in ["otf", "ttf"] {
+			if let Ok(face) = ui::font::open(std::path::Path::new(&format!("{}/.local/share/fonts/{name}.{extension}", std::env::var("HOME").unwrap_or_default()))) {
+				self.font.push((key.clone(), face));
+				return &self.font.iter().find(|(name,_)| name == &key).unwrap().1;
+			}
+		}}
+		ui::text::default_font()[0]
+	}
 	// staff: 0: bass .. 1: treble; step: -8: bottom .. 0: top
 	pub fn y(&self, staff: usize, step: i8) -> i32 { - ((staff as u32 * self.staff_distance) as i32) - step as i32 * (self.staff_height/8) as i32 }
-	pub fn raster<'t>(&'t self, staves: impl Iterator + 't) -> impl Iterator<Item=Rect> + 't {
-		pub fn horizontal(y: i32, dy: u32, x0: i32, x1: i32) -> Rect { Rect{ min: xy{ y: y-(dy/2) as i32, x: x0 }, max: xy{ y: y+(dy/2) as i32, x: x1 } } }
+	// Nearest step to y
+	pub fn step(&self, staff: usize, y: i32) -> i8 { let half = (self.staff_height/8) as i32; ((self.y(staff, 0) - y + half/2).div_euclid(half)) as i8 }
+	pub fn raster<'t>(&'t self, staves: impl Iterator + 't, x0: i32, x1: i32) -> impl Iterator<Item=Rect> + 't {
 		staves.enumerate().map(move |(staff, _)|
-			(0..=8).step_by(2).map(move |step| horizontal(self.y(staff, step), self.engraving_defaults.staff_line_thickness, 0, i32::MAX))
+			(0..=8).step_by(2).map(move |step| horizontal(self.y(staff, step), self.engraving_defaults.staff_line_thickness, x0, x1))
 		).flatten()
 	}
 }
\ No newline at end of file
@@ -0,0 +1,111 @@
+// Lines extending between two elements, possibly across measures and systems
+use {::xy::xy, ui::graphic::vertical, crate::{music_xml::{LineType, Color}, font::SMuFL::{ornament, glissando, dynamic, octave}, sheet::horizontal, measure::Measure}};
+
+#[derive(Debug, Clone, Copy)] pub enum Kind { WavyLine, Tremolo(u8), Glissando(LineType), Slide(LineType), Ending(bool), Wedge{crescendo: bool, spread: i32, niente: bool, color: Option<Color>}, OctaveShift{size: u8, above: bool}, Pedal{notch: bool} }
+impl PartialEq for Kind { fn eq(&self, other: &Self) -> bool { std::mem::discriminant(self) == std::mem::discriminant(other) } } // Stop matches start of the same kind
+
+#[derive(Debug, Clone, Copy)] pub struct Point { pub kind: Kind, pub number: Option<u8>, pub staff: usize, pub step: i8, pub x: i32 }
+#[derive(Debug)] pub enum Event { Start(Point), Stop(Point) }
+
+// Open spanner in system coordinates
+#[derive(Debug)] pub struct Open { pub start: Point, pub continued: bool }
+impl Open { pub fn matches(&self, stop: &Point) -> bool { self.start.kind == stop.kind && self.start.number == stop.number && self.start.staff == stop.staff } }
+
+impl Measure<'_> {
+	// continued: from previous system, continues: to next system
+	pub fn spanner(&mut self, Open{start, continued}: &Open, end: &Point, continues: bool) {
+		match start.kind {
+			Kind::WavyLine => {
+				let width = self.sheet.advance(ornament::wiggle_trill);
+				let mut x = start.x;
+				while x + width <= end.x { self.push_glyph(x as u32, start.staff, start.step, 0, ornament::wiggle_trill); x += width; }
+			}
+			Kind::Tremolo(marks) => { // Strokes between the stems of a two note tremolo
+				let space = (self.sheet.staff_height/4) as i32;
+				let thickness = self.sheet.engraving_defaults.beam_thickness;
+				let (x0, x1) = (start.x + space/2, end.x - space/2);
+				let (y0, y1) = (self.sheet.y(start.staff, start.step), self.sheet.y(end.staff, end.step));
+				for mark in 0..marks as i32 {
+					let dy = (mark - (marks as i32 - 1)/2) * (thickness as i32 + space/4);
+					self.graphic.parallelograms.push(ui::graphic::Parallelogram{top_left: xy{x: x0, y: y0 + dy}, bottom_right: xy{x: x1, y: y1 + dy}, vertical_thickness: thickness});
+				}
+			}
+			Kind::Glissando(line_type)|Kind::Slide(line_type) => { // Between heads
+				let space = (self.sheet.staff_height/4) as i32;
+				let thickness = self.sheet.engraving_defaults.stem_thickness;
+				let (y0, y1) = (self.sheet.y(start.staff, start.step), self.sheet.y(end.staff, end.step));
+				let y = |x: i32| y0 + (y1 - y0) * (x - start.x) / std::cmp::max(1, end.x - start.x);
+				let (dash, gap) = match line_type {
+					LineType::Solid => (end.x - start.x, 0),
+					LineType::Dashed => (space, space/2),
+					LineType::Dotted => (2 * thickness as i32, space/4),
+					LineType::Wavy => {
+						let width = self.sheet.advance(glissando::wiggle);
+						let mut x = start.x;
+						while x + width <= end.x { self.push_glyph(x as u32, start.staff, start.step, y(x + width/2) - y0, glissando::wiggle); x += width; }
+						return;
+					}
+				};
+				let mut x = start.x;
+				while x < end.x {
+					let x1 = std::cmp::min(x + dash, end.x);
+					self.graphic.parallelograms.push(ui::graphic::Parallelogram{top_left: xy{x, y: y(x)}, bottom_right: xy{x: x1, y: y(x1)}, vertical_thickness: thickness});
+					x = x1 + gap;
+				}
+			}
+			Kind::Ending(_) => { // Volta bracket, hooked at its start and at a stop (not a discontinue)
+				let space = (self.sheet.staff_height/4) as i32;
+				let thickness = self.sheet.engraving_defaults.repeat_ending_line_thickness;
+				let y = self.sheet.y(start.staff, start.step);
+				self.graphic.rects.push(horizontal(y, thickness, start.x, end.x));
+				if !continued { self.graphic.rects.push(vertical(start.x + thickness as i32/2, thickness, y, y + 2*space)); }
+				if let (Kind::Ending(true), false) = (end.kind, continues) { self.graphic.rects.push(vertical(end.x - thickness as i32/2, thickness, y, y + 2*space)); }
+			}
+			Kind::Wedge{crescendo, spread, niente, color} => { // Hairpin, half open where split by a system break. Niente circle at the closed end
+				self.set_color(color);
+				let thickness = self.sheet.engraving_defaults.hairpin_thickness;
+				let y = self.sheet.y(start.staff, start.step);
+				let (mut open0, mut open1) = if crescendo { (0, spread) } else { (spread, 0) };
+				if *continued { open0 = spread/2; }
+				if continues { open1 = spread/2; }
+				let niente = if crescendo { niente && !continued } else { matches!(end.kind, Kind::Wedge{niente: true, ..}) && !continues };
+				let width = if niente { self.sheet.advance(dynamic::niente_for_hairpin) } else { 0 };
+				let (x0, x1) = if crescendo { (start.x + width, end.x) } else { (start.x, end.x - width) };
+				for side in [-1, 1] {
+					self.graphic.parallelograms.push(ui::graphic::Parallelogram{top_left: xy{x: x0, y: y + side*open0/2}, bottom_right: xy{x: x1, y: y + side*open1/2}, vertical_thickness: thickness});
+				}
+				if niente {
+					self.push_glyph((if crescendo { start.x } else { x1 }) as u32, start.staff, start.step, self.sheet.center(dynamic::niente_for_hairpin), dynamic::niente_for_hairpin);
+				}
+				self.set_color(None);
+			}
+			Kind::OctaveShift{size, above} => { // 8va (numeral only when continued), dashed line and hook toward the staff
+				let space = (self.sheet.staff_height/4) as i32;
+				let thickness = self.sheet.engraving_defaults.octave_line_thickness;
+				let glyph = if *continued { octave::from(size) } else if above { octave::alta(octave::from(size)) } else { octave::bassa(octave::from(size)) };
+				self.push_glyph(start.x as u32, start.staff, start.step, self.sheet.center(glyph), glyph);
+				let y = self.sheet.y(start.staff, start.step);
+				let mut x = start.x + self.sheet.advance(glyph) + space/4;
+				while x < end.x { self.graphic.rects.push(horizontal(y, thickness, x, std::cmp::min(x + space/2, end.x))); x += space; }
+				if !continues && end.x > start.x { self.graphic.rects.push(vertical(end.x - thickness as i32/2, thickness, if above { y } else { y - space }, if above { y + space } else { y })); }
+			}
+			Kind::Pedal{notch} => { // Bracket line hooked up at its ends, notched (lift and retake) at pedal changes
+				let space = (self.sheet.staff_height/4) as i32;
+				let thickness = self.sheet.engraving_defaults.pedal_line_thickness;
+				let y = self.sheet.y(start.staff, start.step);
+				let end_notch = matches!(end.kind, Kind::Pedal{notch: true});
+				let x0 = if notch && !continued { start.x + space/2 } else { start.x };
+				let x1 = if end_notch && !continues { end.x - space/2 } else { end.x };
+				self.graphic.rects.push(horizontal(y, thickness, x0, x1));
+				if !continued {
+					if notch { self.graphic.parallelograms.push(ui::graphic::Parallelogram{top_left: xy{x: start.x, y: y - space}, bottom_right: xy{x: x0, y}, vertical_thickness: thickness}); }
+					else { self.graphic.rects.push(vertical(start.x + thickness as i32/2, thickness, y - space, y)); }
+				}
+				if !continues {
+					if end_notch { self.graphic.parallelograms.push(ui::graphic::Parallelogram{top_left: xy{x: x1, y}, bottom_right: xy{x: end.x, y: y - space}, vertical_thickness: thickness}); }
+					else { self.graphic.rects.push(vertical(end.x - thickness as i32/2, thickness, y - space, y)); }
+				}
+			}
+		}
+	}
+}
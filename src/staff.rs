@@ -1,6 +1,6 @@
-use {derive_more::{Deref, DerefMut}, vector::MinMax, crate::{music_xml::{self, Clef, ClefSign, Pitch, StemDirection, Note}}};
+use {derive_more::{Deref, DerefMut}, vector::MinMax, crate::{music_xml::{self, Clef, Step, Pitch, StemDirection, Note}}};
 
-#[derive(Default, Debug)] pub struct Staff { pub clef: Option<Clef>, pub octave: i8 }
+#[derive(Default, Debug, Clone)] pub struct Staff { pub clef: Option<Clef>, pub octave: i8, pub polyphonic: bool, pub key: Vec<(Step, f32)> }
 
 impl From<&music_xml::Staff> for usize { fn from(staff: &music_xml::Staff) -> Self { (2 - staff.0) as usize } } // 1..2 -> 1: treble .. 0: bass
 
@@ -19,26 +19,43 @@ impl StaffMut<'_> { pub fn as_ref(&self) -> StaffRef { StaffRef{index: self.inde
 
 impl Staff {
 	#[allow(non_snake_case)]
-	fn C4(&self) -> i8 { use ClefSign::*; (match self.clef.as_ref().unwrap().sign { G=> -2, F=> 10 }) - self.octave*7 }
+	fn C4(&self) -> i8 { self.clef.as_ref().unwrap().C4() - self.octave*7 }
 	pub fn step(&self, pitch: &Pitch) -> i8 { self.C4() + i8::from(pitch) }
+	// Key signature accidentals are in the octave above the lowest step of the conventional pattern of the clef (sharps or flats), shifted from treble
+	pub fn key_step(&self, step: &Step, sharp: bool) -> i8 {
+		let c4 = self.clef.as_ref().unwrap().C4();
+		let shift = (c4 + 5).rem_euclid(7) - 3;
+		let lowest = if sharp { 3 + shift - if shift > 0 { 2 } else { 0 } } else { 1 + shift }; // Tenor and soprano sharps start low
+		lowest + (c4 + i8::from(step) - lowest).rem_euclid(7)
+	}
 }
 
 impl Note {
-    fn step(&self, staves: &[Staff]) -> Option<i8> { self.pitch().map(|pitch| staves.index(&self.staff.unwrap()).step(&pitch)) }
+    pub fn step(&self, staves: &[Staff]) -> Option<i8> { self.pitch().map(|pitch| staves.index(&self.staff.unwrap()).step(&pitch)) }
 }
 
 pub trait Chord {
 	fn staff(&self) -> usize;
     fn bounds(&self, staves: &[Staff]) -> MinMax<i8>;
+    fn has_stem(&self) -> bool;
+    fn stem_directions(&self, default: StemDirection) -> Vec<StemDirection>;
     fn stem_step(&self, staves: &[Staff], direction: StemDirection) -> i8;
 }
 impl Chord for Vec<&Note> {
 	fn staff(&self) -> usize { (&self.first().unwrap().staff.unwrap()).into() }
     #[track_caller] fn bounds(&self, staves: &[Staff]) -> MinMax<i8> {
-        self.iter().filter(|x| x.has_stem()).filter_map(|note| note.step(staves)).map(|e|MinMax{min: e, max: e}).reduce(MinMax::minmax).unwrap()
+        self.iter().filter_map(|note| note.step(staves)).map(|e|MinMax{min: e, max: e}).reduce(MinMax::minmax).unwrap()
     }
-    fn stem_step(&self, staves: &[Staff], direction: StemDirection) -> i8 {
+    fn has_stem(&self) -> bool { self.iter().any(|note| note.has_stem()) }
+    // Explicit <stem> of the first note defining it, otherwise the default (beam group) direction
+    fn stem_directions(&self, default: StemDirection) -> Vec<StemDirection> {
+		if !self.has_stem() { return vec![]; }
+		use StemDirection::*;
+		match self.iter().find_map(|note| note.stem.as_ref()).map(|stem| stem.value).unwrap_or(default) { Double => vec![Up, Down], None => vec![], direction => vec![direction] }
+    }
+    fn stem_step(&self, staves: &[Staff], direction: StemDirection) -> i8 { // Extends stems of notes far outside the staff up to the middle line. Shorter grace and cue stems
 	    let bounds = self.bounds(staves);
-	    if let StemDirection::Down = direction { bounds.min - 5 } else { bounds.max + 5 }
+	    let (length, middle) = if self[0].grace.is_some() { (3, None) } else if self[0].cue.is_some() { (4, Some(4)) } else { (5, Some(4)) };
+	    if let StemDirection::Down = direction { std::cmp::min(bounds.min - length, middle.unwrap_or(i8::MAX)) } else { std::cmp::max(bounds.max + length, middle.unwrap_or(i8::MIN)) }
     }
 }
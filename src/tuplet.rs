@@ -0,0 +1,50 @@
+use {vector::MinMax, ui::graphic::vertical, crate::{music_xml::{Note, Notation, Tuplet, TupletPortion, TimeModification, StartStop, ShowTuplet, Placement}, font::SMuFL::tuplet, sheet::horizontal, measure::MeasureLayoutContext}};
+
+#[derive(Debug)] pub struct Span { voice: (Option<u8>, Option<u8>), number: Option<u8>, staff: usize, x: MinMax<i32>, steps: MinMax<i8>, above: bool, bracket: Option<bool>, show_number: ShowTuplet, ratio: (u8, u8), beam: Option<usize> }
+
+impl MeasureLayoutContext<'_> {
+	// Accumulates the extent of the open tuplets of the chord voice, and draws the tuplets stopping on this chord
+	pub fn tuplet(&mut self, chord: &[&Note], staff: usize, x: MinMax<i32>, steps: MinMax<i8>, stem_up: bool, beam: Option<usize>) {
+		let voice = (chord[0].staff.map(|staff| staff.0), chord[0].voice);
+		let tuplets = move || chord.iter().flat_map(|note| note.notations()).filter_map(|notation| if let Notation::Tuplet(tuplet) = notation { Some(tuplet) } else { None });
+		for Tuplet{number, bracket, show_number, placement, tuplet_actual, tuplet_normal, ..} in tuplets().filter(|tuplet| matches!(tuplet.r#type, StartStop::Start)) {
+			let (actual, normal) = chord[0].time_modification.as_ref().map(|&TimeModification{actual_notes, normal_notes}| (actual_notes, normal_notes)).unwrap_or((3, 2));
+			let portion = |portion: &Option<TupletPortion>| portion.as_ref().and_then(|portion| portion.tuplet_number); // Explicit for nested tuplets
+			self.tuplets.push(Span{voice, number: *number, staff, x: MinMax{min: x.min, max: x.max}, steps: MinMax{min: steps.min, max: steps.max},
+				above: placement.map_or(stem_up, |placement| matches!(placement, Placement::Above)), bracket: *bracket, show_number: show_number.unwrap_or(ShowTuplet::Actual),
+				ratio: (portion(tuplet_actual).unwrap_or(actual), portion(tuplet_normal).unwrap_or(normal)), beam});
+		}
+		for span in self.tuplets.iter_mut().filter(|span| span.voice == voice) {
+			span.x = MinMax{min: std::cmp::min(span.x.min, x.min), max: std::cmp::max(span.x.max, x.max)};
+			span.steps = MinMax{min: std::cmp::min(span.steps.min, steps.min), max: std::cmp::max(span.steps.max, steps.max)};
+			if span.beam != beam { span.beam = None; }
+		}
+		for Tuplet{number, ..} in tuplets().filter(|tuplet| matches!(tuplet.r#type, StartStop::Stop)) {
+			if let Some(index) = self.tuplets.iter().rposition(|span| span.voice == voice && (number.is_none() || &span.number == number)) { // Innermost
+				let span = self.tuplets.remove(index);
+				self.draw_tuplet(span);
+			}
+		}
+	}
+	fn draw_tuplet(&mut self, Span{staff, x: MinMax{min: left, max: right}, steps, above, bracket, show_number, ratio: (actual, normal), beam, ..}: Span) {
+		let space = self.space() as i32;
+		let step = if above { steps.max + 3 } else { steps.min - 3 };
+		let y = self.y(staff, step);
+		let number = match show_number { ShowTuplet::Actual => actual.to_string(), ShowTuplet::Both => format!("{actual}:{normal}"), ShowTuplet::None => String::new() };
+		let number = number.chars().map(|c| if c == ':' { tuplet::colon } else { tuplet::from(c) }).collect::<Vec<_>>();
+		let width = number.iter().map(|&glyph| self.sheet.advance(glyph)).sum::<i32>();
+		let center = (left+right)/2;
+		let dy = self.sheet.bbox(tuplet::zero).y_max as i32 / 2; // Centers digits on the bracket line
+		let mut x = center - width/2;
+		for glyph in number { self.push_glyph(x as u32, staff, step, dy, glyph); x += self.sheet.advance(glyph); }
+		if bracket.unwrap_or(beam.is_none()) { // Number only when the tuplet is a single beam group
+			let thickness = self.sheet.engraving_defaults.tuplet_bracket_thickness;
+			let gap = if width > 0 { width/2 + space/4 } else { 0 };
+			let hook = if above { y + space/2 } else { y - space/2 }; // Toward the notes
+			self.measure.graphic.rects.extend([
+				horizontal(y, thickness, left, center-gap), horizontal(y, thickness, center+gap, right),
+				vertical(left, thickness, std::cmp::min(y, hook), std::cmp::max(y, hook)), vertical(right - thickness as i32, thickness, std::cmp::min(y, hook), std::cmp::max(y, hook))
+			]);
+		}
+	}
+}